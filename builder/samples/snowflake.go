@@ -0,0 +1,22 @@
+package snow
+
+import (
+	"fmt"
+	"strings"
+)
+
+// Snowflake is a single flake of the first snow.
+type Snowflake struct {
+	Arms uint8
+	Name string
+}
+
+const Arms = 6
+
+func (s *Snowflake) Melt(celsius float32) (string, error) {
+	// Nothing happens below zero
+	if celsius < 0.0 {
+		return "", fmt.Errorf("too cold: %v", celsius)
+	}
+	return strings.ToUpper(s.Name) + " melted\n", nil
+}
@@ -0,0 +1,19 @@
+from dataclasses import dataclass
+
+
+@dataclass
+class Snowflake:
+    """A single flake of the first snow."""
+
+    arms: int = 6
+    name: str = "first"
+
+    def melt(self, celsius: float) -> str | None:
+        # Nothing happens below zero
+        if celsius < 0.0:
+            return None
+        return f"{self.name} melted\n"
+
+
+flakes = [Snowflake(name=n) for n in ("a", "b") if n is not None]
+print(len(flakes), True, ...)
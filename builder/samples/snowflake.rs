@@ -0,0 +1,26 @@
+use std::fmt;
+
+/// A single flake of the first snow.
+#[derive(Debug, Clone)]
+pub struct Snowflake {
+    arms: u8,
+    name: &'static str,
+}
+
+impl Snowflake {
+    pub const ARMS: u8 = 6;
+
+    pub fn melt(&self, celsius: f32) -> Option<String> {
+        // Nothing happens below zero
+        if celsius < 0.0 {
+            return None;
+        }
+        Some(format!("{} melted\n", self.name))
+    }
+}
+
+impl fmt::Display for Snowflake {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({} arms)", self.name, self.arms)
+    }
+}
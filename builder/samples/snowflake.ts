@@ -0,0 +1,22 @@
+import { Flake } from "./flake";
+
+type Layer = { depth: number; flakes: Flake[] };
+
+// Flakes settle in the order they fall
+export function settle(flakes: Flake[], depth = 12): Layer[] {
+  return flakes.map((flake, index) => ({
+    ...flake,
+    layer: index % depth,
+    melted: false,
+  }));
+}
+
+export class Drift implements Iterable<Flake> {
+  private readonly wind = /n(orth)?e/i;
+
+  constructor(public flakes: Flake[] = []) {}
+
+  *[Symbol.iterator]() {
+    yield* this.flakes.filter((flake) => `${flake.name}` !== "");
+  }
+}
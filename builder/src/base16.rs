@@ -0,0 +1,276 @@
+use serde_yaml::Value;
+
+use std::{fmt::Write, path::Path};
+
+use crate::{
+    color::Color,
+    export::{OutputFile, ThemeExporter},
+    import::ImportedPalette,
+    theme::{Brightness, Theme, ThemePackage},
+};
+
+/// Token roles on base16 slots, following the base16 styling guidelines
+/// (`base0A` classes and types, `base0D` functions, `base0E` keywords, ...).
+/// `base08` is left to git/diagnostics red, since terminals use it as red;
+/// properties share `base09` with constants, as they do in Snowfall.
+pub const TOKEN_SLOTS: [(&str, &str); 9] = [
+    ("comment", "base03"),
+    ("operators", "base05"),
+    ("constants", "base09"),
+    ("properties", "base09"),
+    ("types", "base0A"),
+    ("strings", "base0B"),
+    ("brackets", "base0C"),
+    ("functions", "base0D"),
+    ("keywords", "base0E"),
+];
+
+/// ANSI colors and their bright versions on base16/base24 slots, as
+/// base16-shell lays them out. Only base24 has slots for the bright colors
+/// other than black and white.
+pub const TERMINAL_SLOTS: [(&str, &str, &str, &str); 8] = [
+    ("black", "base00", "bright_black", "base03"),
+    ("red", "base08", "bright_red", "base12"),
+    ("green", "base0B", "bright_green", "base14"),
+    ("yellow", "base0A", "bright_yellow", "base13"),
+    ("blue", "base0D", "bright_blue", "base16"),
+    ("magenta", "base0E", "bright_magenta", "base17"),
+    ("cyan", "base0C", "bright_cyan", "base15"),
+    ("white", "base05", "bright_white", "base07"),
+];
+
+/// Reads a base16 or base24 YAML scheme, in either the classic flat layout
+/// (`scheme:`, `base00: "24272d"`) or the newer one with a `palette:` map.
+pub fn import(source: &str) -> Result<ImportedPalette, String> {
+    let document: Value = serde_yaml::from_str(source).map_err(|error| error.to_string())?;
+    let palette = match document.get("palette") {
+        Some(palette) => palette,
+        None => &document,
+    };
+    let slot = |name: &str| {
+        palette
+            .get(name)
+            .and_then(Value::as_str)
+            .and_then(Color::parse)
+    };
+
+    let mut imported = ImportedPalette {
+        name: document
+            .get("name")
+            .or_else(|| document.get("scheme"))
+            .and_then(Value::as_str)
+            .map(String::from),
+        background: slot("base00"),
+        foreground: slot("base05"),
+        accent: slot("base0D"),
+        ..Default::default()
+    };
+    imported.brightness = match document.get("variant").and_then(Value::as_str) {
+        Some("light") => Some(Brightness::Light),
+        Some("dark") => Some(Brightness::Dark),
+        _ => None,
+    };
+
+    for (role, name) in TOKEN_SLOTS {
+        if let Some(color) = slot(name) {
+            imported.tokens.insert(role, color);
+        }
+    }
+    let base24 = slot("base12").is_some();
+    for (role, normal, bright_role, bright) in TERMINAL_SLOTS {
+        if let Some(color) = slot(normal) {
+            imported.terminal.insert(role, color);
+        }
+        if base24 || role == "black" || role == "white" {
+            if let Some(color) = slot(bright) {
+                imported.terminal.insert(bright_role, color);
+            }
+        }
+    }
+
+    for (role, name) in [
+        ("added", "base0B"),
+        ("modified", "base0D"),
+        ("removed", "base08"),
+    ] {
+        if let Some(color) = slot(name) {
+            imported.git.insert(role, color);
+        }
+    }
+    for (role, name) in [
+        ("error", "base08"),
+        ("warning", "base09"),
+        ("info", "base0D"),
+    ] {
+        if let Some(color) = slot(name) {
+            imported.diagnostics.insert(role, color);
+        }
+    }
+
+    Ok(imported)
+}
+
+/// The sixteen (or twenty four) base colors of a theme. Token slots follow
+/// [`TOKEN_SLOTS`], except `base05`, which is the foreground, and the first
+/// role wins where roles share a slot.
+pub fn palette(theme: &Theme, base24: bool) -> Vec<(String, Color)> {
+    let extreme = match theme.brightness {
+        Brightness::Dark => Color::from_hex("#ffffff"),
+        Brightness::Light => Color::from_hex("#000000"),
+    };
+    let token = |role: &str| {
+        theme
+            .tokens
+            .colors()
+            .into_iter()
+            .find(|(name, _)| *name == role)
+            .map(|(_, color)| color)
+            .unwrap()
+    };
+
+    let mut palette = vec![
+        ("base00", theme.background),
+        ("base01", theme.secondary_bg()),
+        ("base02", theme.accent.mix(theme.background, 0.8)),
+        ("base03", theme.tokens.comment),
+        ("base04", theme.foreground.mix(theme.background, 0.3)),
+        ("base05", theme.foreground),
+        ("base06", theme.foreground.mix(extreme, 0.3)),
+        ("base07", theme.foreground.mix(extreme, 0.6)),
+        ("base08", theme.git.removed),
+    ];
+    for (role, slot) in TOKEN_SLOTS {
+        if !palette.iter().any(|(name, _)| *name == slot) {
+            palette.push((slot, token(role)));
+        }
+    }
+    palette.push(("base0F", theme.tokens.constants.darken(0.25)));
+
+    if base24 {
+        let terminal = &theme.terminal;
+        palette.extend([
+            ("base10", theme.background.darken(0.1)),
+            ("base11", theme.background.darken(0.2)),
+            ("base12", terminal.bright_red),
+            ("base13", terminal.bright_yellow),
+            ("base14", terminal.bright_green),
+            ("base15", terminal.bright_cyan),
+            ("base16", terminal.bright_blue),
+            ("base17", terminal.bright_magenta),
+        ]);
+    }
+
+    palette.sort_by_key(|(name, _)| *name);
+    palette
+        .into_iter()
+        .map(|(name, color)| (name.to_string(), color))
+        .collect()
+}
+
+/// A base16 or base24 scheme in the tinted-theming YAML layout.
+pub fn export(theme: &Theme, author: &str, base24: bool) -> String {
+    let mut yaml = String::new();
+    let _ = writeln!(
+        yaml,
+        "system: \"{}\"",
+        if base24 { "base24" } else { "base16" }
+    );
+    // JSON strings are valid double-quoted YAML scalars, escapes included.
+    let _ = writeln!(
+        yaml,
+        "name: {}",
+        serde_json::to_string(&theme.name).unwrap()
+    );
+    let _ = writeln!(yaml, "author: {}", serde_json::to_string(author).unwrap());
+    let _ = writeln!(
+        yaml,
+        "variant: \"{}\"",
+        match theme.brightness {
+            Brightness::Dark => "dark",
+            Brightness::Light => "light",
+        }
+    );
+    let _ = writeln!(yaml, "palette:");
+    for (name, color) in palette(theme, base24) {
+        let _ = writeln!(yaml, "  {}: \"{}\"", name, color.with_opacity(1.0).to_hex());
+    }
+
+    yaml
+}
+
+/// base16 or base24 schemes, `dist/base16/<theme>.yaml` or
+/// `dist/base24/<theme>.yaml`, for every theme that has its own colors.
+pub struct Base16Exporter {
+    pub base24: bool,
+}
+
+impl ThemeExporter for Base16Exporter {
+    fn name(&self) -> &str {
+        match self.base24 {
+            true => "base24",
+            false => "base16",
+        }
+    }
+
+    fn extension(&self) -> &str {
+        "yaml"
+    }
+
+    fn export(&self, package: &ThemePackage) -> Vec<OutputFile> {
+        package
+            .themes
+            .iter()
+            .filter(|theme| !theme.is_background_syntax)
+            .map(|theme| OutputFile {
+                path: Path::new("dist")
+                    .join(self.name())
+                    .join(format!("{}.yaml", theme.slug())),
+                contents: export(theme, &package.author, self.base24),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exports_every_slot() {
+        let theme = &crate::snowfall().themes[0];
+
+        assert_eq!(palette(theme, false).len(), 16);
+        assert_eq!(palette(theme, true).len(), 24);
+        assert!(export(theme, "freethinkel", false).contains("  base00: \"#24272d\"\n"));
+    }
+
+    #[test]
+    fn round_trips_through_import() {
+        for theme in crate::snowfall().themes {
+            let payload = import(&export(&theme, "freethinkel", true))
+                .unwrap()
+                .complete("fallback".into())
+                .unwrap();
+
+            assert_eq!(payload.name, theme.name);
+            assert_eq!(payload.brightness, theme.brightness);
+            assert_eq!(payload.background, theme.background);
+            assert_eq!(payload.foreground, theme.foreground);
+            assert_eq!(payload.tokens.strings, theme.tokens.strings);
+            assert_eq!(payload.tokens.keywords, theme.tokens.keywords);
+            assert_eq!(payload.terminal.bright_red, theme.terminal.bright_red);
+        }
+    }
+
+    #[test]
+    fn escapes_names() {
+        let mut theme = crate::snowfall().themes[0].clone();
+        theme.name = "Snowfall \"night\": dark \\ #1".into();
+        let yaml = export(&theme, "A: \"B\"", false);
+        let document = serde_yaml::from_str::<serde_yaml::Value>(&yaml).unwrap();
+
+        assert_eq!(document["name"].as_str(), Some(theme.name.as_str()));
+        assert_eq!(document["author"].as_str(), Some("A: \"B\""));
+        assert_eq!(import(&yaml).unwrap().name, Some(theme.name));
+    }
+}
@@ -1,44 +1,50 @@
-use std::{
-    env::{self, current_dir},
-    fs::{self, File},
-    io::Write,
-    path::Path,
-};
+use std::{env, fs, io, path::Path};
 
 use crate::{
     color::Color,
+    export::{OutputFile, ThemeExporter},
     theme::{Brightness, Theme, ThemePackage},
 };
 use serde::Serialize;
 use serde_json::{to_string_pretty, Map, Value};
 
+/// Writes a theme package through a set of exporters, by default just the
+/// Zed theme.
 pub struct Builder<'a> {
     theme: &'a ThemePackage,
+    exporters: Vec<Box<dyn ThemeExporter>>,
 }
 
 impl<'a> Builder<'a> {
     pub fn new(theme: &'a ThemePackage) -> Self {
-        return Self { theme };
+        Self::with_exporters(theme, vec![Box::new(ZedExporter)])
     }
 
-    pub fn build(&self) {
-        let raw_theme = serde_json::json!({
-          "$schema": "https://zed.dev/schema/themes/v0.2.0.json",
-          "name": self.theme.name,
-          "author": self.theme.author,
-          "themes": self.theme.themes,
-        });
-
-        let dir = current_dir().unwrap();
-        let target_path = dir.join("../themes/theme.json");
+    pub fn with_exporters(theme: &'a ThemePackage, exporters: Vec<Box<dyn ThemeExporter>>) -> Self {
+        Self { theme, exporters }
+    }
 
-        let mut file = File::create(&target_path).unwrap();
-        let _ = file
-            .write(to_string_pretty(&raw_theme).unwrap().as_bytes())
-            .expect("Error writing theme");
+    /// Writes every exporter's files under `root`, the repository root for
+    /// the Snowfall themes. With `PREVIEW=true`, also installs the Zed theme
+    /// into `~/.config/zed/themes`.
+    pub fn build(&self, root: &Path) -> io::Result<()> {
+        for exporter in &self.exporters {
+            for file in exporter.export(self.theme) {
+                let path = root.join(&file.path);
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::write(&path, file.contents)?;
+            }
+        }
 
-        if env::var("PREVIEW").unwrap_or("".into()) == "true" {
-            let home_dir = env::var("HOME").expect("HOME environment variable not set");
+        let zed = self
+            .exporters
+            .iter()
+            .any(|exporter| exporter.name() == "zed");
+        if zed && env::var("PREVIEW").unwrap_or("".into()) == "true" {
+            let home_dir = env::var("HOME")
+                .map_err(|_| io::Error::other("HOME environment variable not set"))?;
             let preview_file = Path::new(&home_dir).join(".config/zed/themes/snowfall.json");
 
             match fs::remove_file(&preview_file) {
@@ -46,18 +52,101 @@ impl<'a> Builder<'a> {
                 Err(e) => println!("Error removing preview file: {}", e),
             }
 
-            let _ = fs::copy(&target_path, preview_file).expect("Error copying theme");
+            fs::copy(root.join(ZED_THEME_PATH), preview_file)?;
         }
+        Ok(())
+    }
+}
+
+const ZED_THEME_PATH: &str = "themes/theme.json";
+
+/// The Zed theme family, `themes/theme.json`.
+pub struct ZedExporter;
+
+impl ZedExporter {
+    /// The theme family as Zed reads it.
+    pub fn json(package: &ThemePackage) -> String {
+        let raw_theme = serde_json::json!({
+          "$schema": "https://zed.dev/schema/themes/v0.2.0.json",
+          "name": package.name,
+          "author": package.author,
+          "themes": package.themes,
+        });
+
+        to_string_pretty(&raw_theme).unwrap()
     }
 }
 
-trait SyntaxTheme {
+impl ThemeExporter for ZedExporter {
+    fn name(&self) -> &str {
+        "zed"
+    }
+
+    fn extension(&self) -> &str {
+        "json"
+    }
+
+    fn export(&self, package: &ThemePackage) -> Vec<OutputFile> {
+        vec![OutputFile {
+            path: ZED_THEME_PATH.into(),
+            contents: Self::json(package),
+        }]
+    }
+}
+
+/// Style a single syntax capture resolves to.
+pub struct SyntaxStyle {
+    pub color: Color,
+    pub background: Option<Color>,
+    pub italic: bool,
+    pub bold: bool,
+}
+
+pub trait SyntaxTheme {
     fn syntax(&self, background_color: bool) -> Value;
+    fn highlight(&self, capture: &str) -> SyntaxStyle;
 }
 
 impl SyntaxTheme for Theme {
+    fn highlight(&self, capture: &str) -> SyntaxStyle {
+        let syntax = self.syntax(self.is_background_syntax);
+
+        // Like Zed, fall back from `comment.doc` to `comment` when a capture
+        // has no style of its own.
+        let mut name = capture;
+        let style = loop {
+            if let Some(style) = syntax.get(name) {
+                break Some(style);
+            }
+            match name.rfind('.') {
+                Some(index) => name = &name[..index],
+                None => break None,
+            }
+        };
+
+        match style {
+            Some(style) => SyntaxStyle {
+                color: style["color"]
+                    .as_str()
+                    .map(Color::from_hex)
+                    .unwrap_or(self.foreground),
+                background: style["background_color"].as_str().map(Color::from_hex),
+                italic: style["font_style"] == "italic",
+                bold: style["font_weight"]
+                    .as_u64()
+                    .is_some_and(|weight| weight >= 600),
+            },
+            None => SyntaxStyle {
+                color: self.foreground,
+                background: None,
+                italic: false,
+                bold: false,
+            },
+        }
+    }
+
     fn syntax(&self, background_color: bool) -> Value {
-        let bg_fields = vec![
+        let bg_fields = [
             "string",
             "function",
             "comment",
@@ -88,7 +177,7 @@ impl SyntaxTheme for Theme {
               "font_weight": null
             },
             "comment.doc": {
-              "color": self.tokens.comment.mix(self.foreground, 0.5),
+              "color": self.doc_comment(),
               "font_style": null,
               "font_weight": null
             },
@@ -428,3 +517,50 @@ impl Serialize for Theme {
         value.serialize(serializer)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn published_theme_is_unchanged() {
+        // Pins every published color. After an intended palette change,
+        // regenerate with `cargo run` and review the diff.
+        let generated = ZedExporter::json(&crate::snowfall());
+        let published = include_str!("../../themes/theme.json");
+
+        for (line, (generated, published)) in generated.lines().zip(published.lines()).enumerate() {
+            assert_eq!(generated, published, "themes/theme.json:{}", line + 1);
+        }
+        assert_eq!(generated.lines().count(), published.lines().count());
+    }
+
+    #[test]
+    fn highlight_falls_back_to_parent_capture() {
+        let theme = &crate::snowfall().themes[0];
+
+        assert_eq!(
+            theme.highlight("punctuation.bracket.angle").color,
+            theme.tokens.brackets
+        );
+        assert_eq!(
+            theme.highlight("string.escape").color,
+            theme.tokens.constants
+        );
+        assert_eq!(theme.highlight("operator").color, theme.foreground);
+        assert!(theme.highlight("type.builtin").bold);
+        assert!(theme.highlight("hint").italic);
+    }
+
+    #[test]
+    fn highlight_reports_background_for_bg_syntax() {
+        let theme = crate::snowfall().themes[1].clone();
+
+        assert!(theme.is_background_syntax);
+        assert_eq!(
+            theme.highlight("string").background,
+            Some(theme.tokens.strings.with_opacity(0.2))
+        );
+        assert_eq!(theme.highlight("keyword").background, None);
+    }
+}
@@ -1,9 +1,12 @@
 use std::str::FromStr;
 
-use palette::{MixAssign, Srgba};
-use serde::Serialize;
+use palette::{
+    color_difference::Ciede2000, Clamp, FromColor, Lab, LinSrgb, MixAssign, Oklab, Oklch, Srgb,
+    Srgba,
+};
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct Color {
     value: Srgba<u8>,
 }
@@ -18,30 +21,217 @@ impl Color {
 
         let value = Srgba::from_str(&hex).unwrap();
 
-        return Self { value };
+        Self { value }
+    }
+
+    /// Lenient parser for user-supplied colors: accepts `#rgb`, `#rgba`,
+    /// `#rrggbb` and `#rrggbbaa`, with or without the leading `#`.
+    pub fn parse(hex: &str) -> Option<Self> {
+        let digits = hex.trim().trim_start_matches('#');
+        if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+
+        let digits = match digits.len() {
+            3 | 4 => digits.chars().flat_map(|c| [c, c]).collect::<String>(),
+            6 | 8 => digits.to_string(),
+            _ => return None,
+        };
+        let channel = |index: usize| u8::from_str_radix(&digits[index..index + 2], 16).ok();
+
+        Some(Self {
+            value: Srgba::new(
+                channel(0)?,
+                channel(2)?,
+                channel(4)?,
+                if digits.len() == 8 { channel(6)? } else { 255 },
+            ),
+        })
+    }
+
+    pub fn to_hex(self) -> String {
+        let color = self.value;
+
+        match color.alpha {
+            255 => format!("#{:02x}{:02x}{:02x}", color.red, color.green, color.blue),
+            _ => format!(
+                "#{:02x}{:02x}{:02x}{:02x}",
+                color.red, color.green, color.blue, color.alpha
+            ),
+        }
+    }
+
+    /// A CSS color, `rgba()` when translucent.
+    pub fn to_css(self) -> String {
+        match self.alpha() {
+            1.0 => self.to_hex(),
+            _ => {
+                let [red, green, blue] = self.rgb();
+                format!("rgba({}, {}, {}, {:.3})", red, green, blue, self.alpha())
+            }
+        }
+    }
+
+    /// 8-bit red, green and blue channels, ignoring alpha.
+    pub fn rgb(self) -> [u8; 3] {
+        [self.value.red, self.value.green, self.value.blue]
+    }
+
+    /// Alpha channel as a `0.0..=1.0` fraction.
+    pub fn alpha(self) -> f32 {
+        self.value.alpha as f32 / 255.0
     }
 
     pub fn with_opacity(&self, amount: f32) -> Self {
-        let mut color: Srgba<u8> = Srgba::from(self.value);
-        let alpha = (amount * 255.0) as u8;
-        color.alpha = alpha;
+        let mut color = self.value;
+        // Truncate rather than round so the published palette keeps its alpha values.
+        color.alpha = (amount.clamp(0.0, 1.0) * 255.0) as u8;
 
-        return Self { value: color };
+        Self { value: color }
     }
 
     pub fn mix(&self, other: Color, amount: f32) -> Self {
-        // Convert self and other colors to Rgba<f32> for mixing
-        let mut new_color = self.value.into_format().clone();
-        new_color.mix_assign(other.value.into_format(), amount);
-        // let new_color = self.value.into_format().mix_assign(other, amount * 255);
+        // Mix in `0.0..=1.0` floats and convert back, exactly as the palette
+        // was first published; rounding in u8 space shifts some colors by one.
+        let mut color: Srgba<f32> = self.value.into_format();
+        color.mix_assign(other.value.into_format(), amount.clamp(0.0, 1.0));
 
-        return Self {
-            value: new_color.into(),
-        };
+        Self {
+            value: color.into_format(),
+        }
     }
 
     pub fn darken(&self, amount: f32) -> Self {
-        return self.mix(Color::from_hex("#000000"), amount);
+        self.mix(Color::from_hex("#000000"), amount)
+    }
+
+    /// Linear-light RGB channels in `0.0..=1.0`, ignoring alpha.
+    pub fn to_linear(self) -> [f32; 3] {
+        let rgb: Srgb<f32> = self.value.color.into_format();
+        let linear = rgb.into_linear();
+
+        [linear.red, linear.green, linear.blue]
+    }
+
+    /// Builds a color from linear-light RGB, clamping out-of-gamut channels.
+    pub fn from_linear(rgb: [f32; 3], alpha: u8) -> Self {
+        let [red, green, blue] = rgb;
+        let linear = LinSrgb::new(red, green, blue).clamp();
+        let rgb: Srgb<u8> = Srgb::from_linear(linear);
+
+        Self {
+            value: Srgba::new(rgb.red, rgb.green, rgb.blue, alpha),
+        }
+    }
+
+    /// Perceptual CIEDE2000 difference between two colors, ignoring alpha.
+    /// Around 2 is barely noticeable, under 10 reads as "the same color" at a glance.
+    pub fn delta_e(self, other: Color) -> f32 {
+        self.to_lab().difference(other.to_lab())
+    }
+
+    /// OKLab lightness and `a`/`b` axes.
+    pub fn to_oklab(self) -> [f32; 3] {
+        let rgb: Srgb<f32> = self.value.color.into_format();
+        let oklab = Oklab::from_color(rgb);
+
+        [oklab.l, oklab.a, oklab.b]
+    }
+
+    /// OKLCH lightness (`0.0..=1.0`), chroma and hue in degrees.
+    pub fn to_oklch(self) -> (f32, f32, f32) {
+        let rgb: Srgb<f32> = self.value.color.into_format();
+        let oklch = Oklch::from_color(rgb);
+
+        (oklch.l, oklch.chroma, oklch.hue.into_positive_degrees())
+    }
+
+    /// Builds a color from OKLCH, clamping it into the sRGB gamut.
+    pub fn from_oklch(lightness: f32, chroma: f32, hue: f32, alpha: u8) -> Self {
+        let oklch = Oklch::new(lightness.clamp(0.0, 1.0), chroma.max(0.0), hue);
+        let rgb: Srgb<u8> = Srgb::from_color(oklch).clamp().into_format();
+
+        Self {
+            value: Srgba::new(rgb.red, rgb.green, rgb.blue, alpha),
+        }
+    }
+
+    /// Rotates the OKLCH hue, keeping lightness, chroma and alpha.
+    pub fn rotate_hue(self, degrees: f32) -> Self {
+        let (lightness, chroma, hue) = self.to_oklch();
+        Self::from_oklch(lightness, chroma, hue + degrees, self.value.alpha)
+    }
+
+    /// Multiplies the OKLCH chroma: `0.0` is gray, above `1.0` more vivid.
+    pub fn scale_chroma(self, factor: f32) -> Self {
+        let (lightness, chroma, hue) = self.to_oklch();
+        Self::from_oklch(lightness, chroma * factor, hue, self.value.alpha)
+    }
+
+    /// Moves OKLCH lightness by `amount`, keeping hue, chroma and alpha.
+    /// Negative amounts darken.
+    pub fn oklch_lighten(self, amount: f32) -> Self {
+        let (lightness, chroma, hue) = self.to_oklch();
+        Self::from_oklch(lightness + amount, chroma, hue, self.value.alpha)
+    }
+
+    /// Moves the white point along OKLab's blue-yellow axis, with a little
+    /// red to keep warm shifts from turning green: positive is warmer.
+    pub fn shift_temperature(self, amount: f32) -> Self {
+        let [lightness, a, b] = self.to_oklab();
+        let (a, b) = (a + amount * 0.25, b + amount);
+
+        Self::from_oklch(
+            lightness,
+            a.hypot(b),
+            b.atan2(a).to_degrees(),
+            self.value.alpha,
+        )
+    }
+
+    /// Composites this color over `background` ("source over"), the way it
+    /// ends up on screen when Zed draws a translucent style on top of a surface.
+    pub fn over(self, background: Color) -> Self {
+        let (fg, bg) = (self.value, background.value);
+        let fg_alpha = fg.alpha as f32 / 255.0;
+        let bg_alpha = bg.alpha as f32 / 255.0;
+        let alpha = fg_alpha + bg_alpha * (1.0 - fg_alpha);
+
+        if alpha == 0.0 {
+            return Self {
+                value: Srgba::new(0, 0, 0, 0),
+            };
+        }
+
+        let channel = |fg: u8, bg: u8| -> u8 {
+            let value = (fg as f32 * fg_alpha + bg as f32 * bg_alpha * (1.0 - fg_alpha)) / alpha;
+            value.round().clamp(0.0, 255.0) as u8
+        };
+
+        Self {
+            value: Srgba::new(
+                channel(fg.red, bg.red),
+                channel(fg.green, bg.green),
+                channel(fg.blue, bg.blue),
+                (alpha * 255.0).round() as u8,
+            ),
+        }
+    }
+
+    /// WCAG 2 contrast ratio (`1.0..=21.0`) between two opaque colors.
+    pub fn contrast(self, other: Color) -> f32 {
+        let luminance = |color: Color| {
+            let [red, green, blue] = color.to_linear();
+            0.2126 * red + 0.7152 * green + 0.0722 * blue
+        };
+        let (a, b) = (luminance(self), luminance(other));
+
+        (a.max(b) + 0.05) / (a.min(b) + 0.05)
+    }
+
+    fn to_lab(self) -> Lab {
+        let rgb: Srgb<f32> = self.value.color.into_format();
+        Lab::from_color(rgb)
     }
 }
 
@@ -50,17 +240,249 @@ impl Serialize for Color {
     where
         S: serde::Serializer,
     {
-        // TO HEX
-        let color: Srgba<u8> = self.value;
+        serializer.serialize_str(&self.to_hex())
+    }
+}
 
-        let hex = match color.alpha {
-            255 => format!("#{:02x}{:02x}{:02x}", color.red, color.green, color.blue),
-            _ => format!(
-                "#{:02x}{:02x}{:02x}{:02x}",
-                color.red, color.green, color.blue, color.alpha
-            ),
-        };
+impl<'de> Deserialize<'de> for Color {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let hex = String::deserialize(deserializer)?;
+        Color::parse(&hex)
+            .ok_or_else(|| serde::de::Error::custom(format!("invalid color {:?}", hex)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn color() -> impl Strategy<Value = Color> {
+        any::<[u8; 4]>().prop_map(|[red, green, blue, alpha]| Color {
+            value: Srgba::new(red, green, blue, alpha),
+        })
+    }
+
+    fn opaque_color() -> impl Strategy<Value = Color> {
+        color().prop_map(|color| color.with_opacity(1.0))
+    }
+
+    fn luminance(color: Color) -> u32 {
+        let value = color.value;
+        value.red as u32 + value.green as u32 + value.blue as u32
+    }
+
+    #[test]
+    fn black_on_white_is_21_to_1() {
+        let contrast = Color::from_hex("#000000").contrast(Color::from_hex("#ffffff"));
+        assert!((contrast - 21.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn parses_short_and_long_hex() {
+        assert_eq!(Color::from_hex("#84c4df").to_hex(), "#84c4df");
+        assert_eq!(Color::from_hex("#84C4DF").to_hex(), "#84c4df");
+        assert_eq!(Color::from_hex("#84c4df33").to_hex(), "#84c4df33");
+        assert_eq!(Color::from_hex("#84c4dfff").to_hex(), "#84c4df");
+    }
+
+    #[test]
+    fn parses_user_supplied_colors() {
+        assert_eq!(Color::parse("#abc"), Some(Color::from_hex("#aabbcc")));
+        assert_eq!(Color::parse("abcd"), Some(Color::from_hex("#aabbccdd")));
+        assert_eq!(Color::parse(" #84C4DF "), Some(Color::from_hex("#84c4df")));
+        assert_eq!(Color::parse("#84c4dg"), None);
+        assert_eq!(Color::parse("#84c4d"), None);
+    }
+
+    #[test]
+    fn keeps_published_alpha_values() {
+        let accent = Color::from_hex("#84c4df");
+
+        assert_eq!(accent.with_opacity(0.2).to_hex(), "#84c4df33");
+        assert_eq!(accent.with_opacity(0.15).to_hex(), "#84c4df26");
+        assert_eq!(accent.with_opacity(0.1).to_hex(), "#84c4df19");
+    }
+
+    #[test]
+    fn composites_translucent_colors() {
+        let red = Color::from_hex("#ff000080");
+
+        assert_eq!(red.over(Color::from_hex("#0000ff")).to_hex(), "#80007f");
+        assert_eq!(red.over(Color::from_hex("#0000ff80")).to_hex(), "#aa0055c0");
+        assert_eq!(red.over(Color::from_hex("#00000000")), red);
+        assert_eq!(
+            Color::from_hex("#00000000")
+                .over(Color::from_hex("#00000000"))
+                .to_hex(),
+            "#00000000"
+        );
+    }
+
+    #[test]
+    fn mixes_like_the_published_palette() {
+        // The light theme's focused border: the .5 ties in red and green
+        // would move by one if mixed and rounded in u8 space.
+        let border = Color::from_hex("#f3f9fa");
+
+        assert_eq!(
+            border.mix(Color::from_hex("#88c0d0"), 0.5).to_hex(),
+            "#bedde5"
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn hex_round_trips(color in color()) {
+            prop_assert_eq!(Color::from_hex(&color.to_hex()), color);
+        }
+
+        #[test]
+        fn parse_round_trips(color in color()) {
+            prop_assert_eq!(Color::parse(&color.to_hex()), Some(color));
+            let json = serde_json::to_string(&color).unwrap();
+            prop_assert_eq!(serde_json::from_str::<Color>(&json).unwrap(), color);
+        }
+
+        #[test]
+        fn serializes_as_hex(color in color()) {
+            let value = serde_json::to_value(color).unwrap();
+            prop_assert_eq!(value, serde_json::Value::String(color.to_hex()));
+        }
+
+        #[test]
+        fn mix_endpoints(a in color(), b in color()) {
+            prop_assert_eq!(a.mix(b, 0.0), a);
+            prop_assert_eq!(a.mix(b, 1.0), b);
+            prop_assert_eq!(a.mix(a, 0.5), a);
+        }
+
+        #[test]
+        fn mix_stays_between_inputs(a in color(), b in color(), amount in 0.0f32..=1.0) {
+            let mixed = a.mix(b, amount).value;
+            let (a, b) = (a.value, b.value);
+            for (m, x, y) in [
+                (mixed.red, a.red, b.red),
+                (mixed.green, a.green, b.green),
+                (mixed.blue, a.blue, b.blue),
+                (mixed.alpha, a.alpha, b.alpha),
+            ] {
+                prop_assert!(x.min(y) <= m && m <= x.max(y));
+            }
+        }
+
+        #[test]
+        fn mix_clamps_amount(a in color(), b in color(), amount in 1.0f32..100.0) {
+            prop_assert_eq!(a.mix(b, amount), b);
+            prop_assert_eq!(a.mix(b, -amount), a);
+        }
+
+        #[test]
+        fn darkening_is_monotonic(color in color(), x in 0.0f32..=1.0, y in 0.0f32..=1.0) {
+            let (less, more) = if x <= y { (x, y) } else { (y, x) };
+            prop_assert!(luminance(color.darken(more)) <= luminance(color.darken(less)));
+            prop_assert!(luminance(color.darken(less)) <= luminance(color));
+            prop_assert_eq!(luminance(color.darken(1.0)), 0);
+        }
+
+        #[test]
+        fn opacity_is_clamped(color in color(), amount in -10.0f32..10.0) {
+            let alpha = color.with_opacity(amount).value.alpha;
+            if amount <= 0.0 {
+                prop_assert_eq!(alpha, 0);
+            } else if amount >= 1.0 {
+                prop_assert_eq!(alpha, 255);
+            }
+            let rgb = color.with_opacity(amount).value;
+            prop_assert_eq!(
+                (rgb.red, rgb.green, rgb.blue),
+                (color.value.red, color.value.green, color.value.blue)
+            );
+        }
+
+        #[test]
+        fn linear_round_trips(color in color()) {
+            prop_assert_eq!(Color::from_linear(color.to_linear(), color.value.alpha), color);
+        }
+
+        #[test]
+        fn full_hue_turn_is_identity(color in opaque_color()) {
+            prop_assert!(color.rotate_hue(360.0).delta_e(color) < 0.5);
+        }
+
+        #[test]
+        fn zero_chroma_is_gray(color in opaque_color()) {
+            let [_, a, b] = color.scale_chroma(0.0).to_oklab();
+            prop_assert!(a.abs() < 0.01 && b.abs() < 0.01);
+        }
+
+        #[test]
+        fn transforms_keep_alpha(color in color(), amount in -0.05f32..0.05) {
+            prop_assert_eq!(color.shift_temperature(amount).value.alpha, color.value.alpha);
+            prop_assert_eq!(color.scale_chroma(1.0 + amount).value.alpha, color.value.alpha);
+            prop_assert_eq!(color.rotate_hue(amount * 1000.0).value.alpha, color.value.alpha);
+        }
+
+        #[test]
+        fn oklch_round_trips(color in color()) {
+            let (lightness, chroma, hue) = color.to_oklch();
+            let back = Color::from_oklch(lightness, chroma, hue, color.value.alpha);
+            prop_assert!(back.delta_e(color) < 0.5);
+            prop_assert_eq!(back.value.alpha, color.value.alpha);
+        }
+
+        #[test]
+        fn delta_e_is_a_distance(a in color(), b in color()) {
+            prop_assert!(a.delta_e(a) < 1e-3);
+            prop_assert!((a.delta_e(b) - b.delta_e(a)).abs() < 1e-2);
+            prop_assert!(a.delta_e(b) >= 0.0);
+        }
+
+        #[test]
+        fn fading_out_keeps_rgb(color in opaque_color(), amount in 0.0f32..=1.0) {
+            let faded = color.mix(color.with_opacity(0.0), amount).value;
+            prop_assert_eq!(
+                (faded.red, faded.green, faded.blue),
+                (color.value.red, color.value.green, color.value.blue)
+            );
+            let expected = 255.0 * (1.0 - amount);
+            prop_assert!((faded.alpha as f32 - expected).abs() <= 0.5);
+        }
+
+        #[test]
+        fn opaque_over_anything_is_itself(fg in opaque_color(), bg in color()) {
+            prop_assert_eq!(fg.over(bg), fg);
+        }
+
+        #[test]
+        fn transparent_over_anything_is_background(fg in color(), bg in opaque_color()) {
+            prop_assert_eq!(fg.with_opacity(0.0).over(bg), bg);
+        }
+
+        #[test]
+        fn compositing_over_opaque_matches_mix(fg in opaque_color(), bg in opaque_color(), amount in 0.0f32..=1.0) {
+            let translucent = fg.with_opacity(amount);
+            let alpha = translucent.value.alpha as f32 / 255.0;
+            let composited = translucent.over(bg);
+            let expected = bg.mix(fg, alpha);
+
+            prop_assert_eq!(composited.value.alpha, 255);
+            for (c, e) in [
+                (composited.value.red, expected.value.red),
+                (composited.value.green, expected.value.green),
+                (composited.value.blue, expected.value.blue),
+            ] {
+                prop_assert!((c as i16 - e as i16).abs() <= 1);
+            }
+        }
 
-        serializer.serialize_str(&hex)
+        #[test]
+        fn contrast_is_symmetric_and_bounded(a in opaque_color(), b in opaque_color()) {
+            prop_assert!((a.contrast(b) - b.contrast(a)).abs() < 1e-4);
+            prop_assert!((1.0..=21.01).contains(&a.contrast(b)));
+        }
     }
 }
@@ -0,0 +1,319 @@
+use std::fmt::Display;
+
+use crate::{
+    color::Color,
+    theme::{Theme, ThemeDiagnostic, ThemeGit},
+};
+
+// Okabe & Ito's palette, which stays distinguishable with every common deficiency.
+const SAFE_BLUE: &str = "#0072b2";
+const SAFE_SKY_BLUE: &str = "#56b4e9";
+const SAFE_ORANGE: &str = "#e69f00";
+const SAFE_VERMILLION: &str = "#d55e00";
+const SAFE_YELLOW: &str = "#f0e442";
+const SAFE_BLUISH_GREEN: &str = "#009e73";
+const SAFE_REDDISH_PURPLE: &str = "#cc79a7";
+const SAFE_HUES: [&str; 7] = [
+    SAFE_SKY_BLUE,
+    SAFE_VERMILLION,
+    SAFE_YELLOW,
+    SAFE_BLUE,
+    SAFE_ORANGE,
+    SAFE_BLUISH_GREEN,
+    SAFE_REDDISH_PURPLE,
+];
+
+/// Below this CIEDE2000 distance two colors read as the same hue at a glance.
+pub const COLLAPSE_THRESHOLD: f32 = 10.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Deficiency {
+    Protanopia,
+    Deuteranopia,
+    Tritanopia,
+}
+
+impl Deficiency {
+    pub const ALL: [Deficiency; 3] = [
+        Deficiency::Protanopia,
+        Deficiency::Deuteranopia,
+        Deficiency::Tritanopia,
+    ];
+
+    /// Machado, Oliveira & Fernandes (2009) matrices at full severity, applied
+    /// to linear RGB.
+    fn matrix(self) -> [[f32; 3]; 3] {
+        match self {
+            Deficiency::Protanopia => [
+                [0.152286, 1.052583, -0.204868],
+                [0.114503, 0.786281, 0.099216],
+                [-0.003882, -0.048116, 1.051998],
+            ],
+            Deficiency::Deuteranopia => [
+                [0.367322, 0.860646, -0.227968],
+                [0.280085, 0.672501, 0.047413],
+                [-0.011820, 0.042940, 0.968881],
+            ],
+            Deficiency::Tritanopia => [
+                [1.255528, -0.076749, -0.178779],
+                [-0.078411, 0.930809, 0.146171],
+                [0.004733, 0.691367, 0.303900],
+            ],
+        }
+    }
+
+    pub fn simulate(self, color: Color) -> Color {
+        let rgb = color.to_linear();
+        let matrix = self.matrix();
+        let row = |row: [f32; 3]| row[0] * rgb[0] + row[1] * rgb[1] + row[2] * rgb[2];
+        let alpha = (color.alpha() * 255.0).round() as u8;
+
+        Color::from_linear([row(matrix[0]), row(matrix[1]), row(matrix[2])], alpha)
+    }
+
+    /// The whole theme as seen with this deficiency, for rendering previews.
+    pub fn simulate_theme(self, theme: &Theme) -> Result<Theme, String> {
+        theme.map_colors(format!("{} ({})", theme.name, self), |color| {
+            self.simulate(color)
+        })
+    }
+}
+
+impl Display for Deficiency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Deficiency::Protanopia => "protanopia",
+            Deficiency::Deuteranopia => "deuteranopia",
+            Deficiency::Tritanopia => "tritanopia",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Two colors that mean different things but become indistinguishable.
+pub struct Collapse {
+    pub deficiency: Deficiency,
+    pub first: (String, Color),
+    pub second: (String, Color),
+    /// Distance with typical vision.
+    pub original: f32,
+    /// Distance after simulating the deficiency.
+    pub simulated: f32,
+}
+
+/// Colors that carry meaning only through hue, grouped by what they mean.
+fn semantic_groups(theme: &Theme) -> Vec<Vec<(String, Color)>> {
+    let named = |prefix: &str, colors: Vec<(&str, Color)>| {
+        colors
+            .into_iter()
+            .map(|(name, color)| (format!("{}.{}", prefix, name), color))
+            .collect::<Vec<_>>()
+    };
+    let terminal = |names: &[&str]| {
+        named("terminal", theme.terminal.colors())
+            .into_iter()
+            .filter(|(name, _)| names.contains(&&name["terminal.".len()..]))
+            .collect::<Vec<_>>()
+    };
+
+    vec![
+        named("git", theme.git.colors()),
+        named("diagnostics", theme.diagnostics.colors()),
+        terminal(&["red", "green", "yellow"]),
+        terminal(&["bright_red", "bright_green", "bright_yellow"]),
+    ]
+}
+
+/// Pairs of semantically distinct colors that fall under
+/// [`COLLAPSE_THRESHOLD`] for any simulated deficiency.
+pub fn collapses(theme: &Theme) -> Vec<Collapse> {
+    let groups = semantic_groups(theme);
+    let mut result = vec![];
+
+    for deficiency in Deficiency::ALL {
+        for group in &groups {
+            for (index, first) in group.iter().enumerate() {
+                for second in &group[index + 1..] {
+                    let simulated = deficiency
+                        .simulate(first.1)
+                        .delta_e(deficiency.simulate(second.1));
+
+                    if simulated < COLLAPSE_THRESHOLD {
+                        result.push(Collapse {
+                            deficiency,
+                            first: first.clone(),
+                            second: second.clone(),
+                            original: first.1.delta_e(second.1),
+                            simulated,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    result
+}
+
+/// Moves `color` onto the hue of `target`, keeping its own lightness (give or
+/// take `lighten`) so the theme's mood survives. Chroma is kept too, but never
+/// drops below half of the target's so muted colors still read as their new hue.
+fn rehue(color: Color, target: &str, lighten: f32) -> Color {
+    let (lightness, chroma, _) = color.to_oklch();
+    let (_, target_chroma, hue) = Color::from_hex(target).to_oklch();
+    let chroma = chroma.max(target_chroma * 0.5);
+    let alpha = (color.alpha() * 255.0).round() as u8;
+
+    Color::from_oklch(lightness + lighten, chroma, hue, alpha)
+}
+
+fn collapses_for_anyone(a: Color, b: Color) -> bool {
+    Deficiency::ALL.iter().any(|deficiency| {
+        deficiency.simulate(a).delta_e(deficiency.simulate(b)) < COLLAPSE_THRESHOLD
+    })
+}
+
+/// Rehues `color` onto `preferred`, or else the first safe hue (nudging the
+/// lightness if needed) that stays apart from every color in `taken`.
+fn rehue_apart(color: Color, preferred: &str, taken: &[Color]) -> Color {
+    [0.0, -0.12, 0.12]
+        .into_iter()
+        .flat_map(|lighten| {
+            std::iter::once(preferred)
+                .chain(SAFE_HUES)
+                .map(move |target| rehue(color, target, lighten))
+        })
+        .find(|candidate| {
+            taken
+                .iter()
+                .all(|other| !collapses_for_anyone(*other, *candidate))
+        })
+        .unwrap_or_else(|| rehue(color, preferred, 0.0))
+}
+
+/// Colorblind-friendly copy of a theme: git and diagnostics colors move onto
+/// blue/orange style hues, and syntax tokens that only differ by a red/green
+/// hue get a safe hue of their own. Surfaces and everything else stay as-is.
+pub fn safe_variant(theme: &Theme) -> Result<Theme, String> {
+    let mut variant = theme.clone();
+    variant.name = format!("{} (colorblind)", theme.name);
+
+    let added = rehue_apart(theme.git.added, SAFE_SKY_BLUE, &[]);
+    let removed = rehue_apart(theme.git.removed, SAFE_VERMILLION, &[added]);
+    let modified = rehue_apart(theme.git.modified, SAFE_YELLOW, &[added, removed]);
+    variant.git = ThemeGit {
+        added,
+        modified,
+        removed,
+    };
+
+    let error = rehue_apart(theme.diagnostics.error, SAFE_VERMILLION, &[]);
+    let info = rehue_apart(theme.diagnostics.info, SAFE_BLUE, &[error]);
+    let warning = rehue_apart(theme.diagnostics.warning, SAFE_YELLOW, &[error, info]);
+    variant.diagnostics = ThemeDiagnostic {
+        error,
+        warning,
+        info,
+    };
+
+    // Token colors that were meant to differ but collapse for someone move
+    // onto a safe hue. Roles sharing a color keep sharing it.
+    let mut remapped: Vec<(Color, Color)> = vec![];
+    for (name, color) in theme.tokens.colors() {
+        if name == "comment" || remapped.iter().any(|(original, _)| *original == color) {
+            continue;
+        }
+
+        let distinct = remapped
+            .iter()
+            .filter(|(original, _)| original.delta_e(color) >= COLLAPSE_THRESHOLD)
+            .map(|(_, target)| *target)
+            .collect::<Vec<_>>();
+        let target = match distinct
+            .iter()
+            .any(|other| collapses_for_anyone(*other, color))
+        {
+            true => rehue_apart(color, SAFE_BLUISH_GREEN, &distinct),
+            false => color,
+        };
+        remapped.push((color, target));
+    }
+    variant.tokens = theme.tokens.map(|color| {
+        remapped
+            .iter()
+            .find(|(original, _)| *original == color)
+            .map_or(color, |(_, target)| *target)
+    });
+
+    // Roles that moved to a new hue are no longer set from their named color.
+    let roles = variant.payload().roles();
+    variant.names.retain(|role, name| {
+        roles
+            .iter()
+            .any(|(other, color)| other == role && variant.colors.get(name) == Some(color))
+    });
+    variant.update_surfaces()?;
+    Ok(variant)
+}
+
+pub fn report(theme: &Theme) -> String {
+    let collapses = collapses(theme);
+    let mut report = format!("{}\n", theme.name);
+
+    if collapses.is_empty() {
+        report.push_str("  no collapsing pairs\n");
+    }
+    for collapse in collapses {
+        report.push_str(&format!(
+            "  {:<13} {} {} vs {} {}: ΔE {:.1} -> {:.1}\n",
+            collapse.deficiency.to_string(),
+            collapse.first.0,
+            collapse.first.1.to_hex(),
+            collapse.second.0,
+            collapse.second.1.to_hex(),
+            collapse.original,
+            collapse.simulated,
+        ));
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grays_are_unaffected() {
+        for deficiency in Deficiency::ALL {
+            for hex in ["#000000", "#ffffff", "#808080"] {
+                let gray = Color::from_hex(hex);
+                assert!(deficiency.simulate(gray).delta_e(gray) < 1.0);
+            }
+        }
+    }
+
+    #[test]
+    fn safe_variant_keeps_git_and_diagnostics_apart() {
+        for theme in crate::snowfall().themes {
+            let variant = safe_variant(&theme).unwrap();
+
+            assert!(variant.name.ends_with("(colorblind)"));
+            assert!(!collapses(&variant)
+                .iter()
+                .any(|collapse| !collapse.first.0.starts_with("terminal.")));
+            assert_eq!(variant.background, theme.background);
+        }
+    }
+
+    #[test]
+    fn red_green_collapse_for_deuteranopia() {
+        let theme = &crate::snowfall().themes[0];
+
+        assert!(collapses(theme).iter().any(|collapse| {
+            collapse.deficiency == Deficiency::Deuteranopia
+                && collapse.first.0 == "git.added"
+                && collapse.second.0 == "git.removed"
+        }));
+    }
+}
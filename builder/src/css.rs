@@ -0,0 +1,350 @@
+use std::{fmt::Write, path::Path};
+
+use crate::{
+    color::Color,
+    export::{OutputFile, ThemeExporter},
+    theme::{Brightness, Theme, ThemePackage},
+};
+
+/// Tailwind shade names and the OKLCH lightness of each.
+pub const SHADES: [(u16, f32); 11] = [
+    (50, 0.97),
+    (100, 0.93),
+    (200, 0.87),
+    (300, 0.8),
+    (400, 0.72),
+    (500, 0.64),
+    (600, 0.56),
+    (700, 0.48),
+    (800, 0.4),
+    (900, 0.32),
+    (950, 0.24),
+];
+
+/// Shades 50 to 950 of a color at fixed lightness steps, keeping its hue.
+/// Chroma tapers toward both ends so the light and dark shades stay in
+/// sRGB, and the shade closest in lightness is the color itself.
+pub fn ramp(color: Color) -> Vec<(u16, Color)> {
+    let (lightness, chroma, hue) = color.to_oklch();
+    let distance = |shade: &&(u16, f32)| (shade.1 - lightness).abs();
+    let (closest, _) = SHADES
+        .iter()
+        .min_by(|a, b| distance(a).total_cmp(&distance(b)))
+        .unwrap();
+
+    SHADES
+        .iter()
+        .map(|&(shade, step)| match shade == *closest {
+            true => (shade, color),
+            false => {
+                let taper = (1.0 - ((step - 0.62) / 0.45).powi(2)).max(0.25);
+                (shade, Color::from_oklch(step, chroma * taper, hue, 255))
+            }
+        })
+        .collect()
+}
+
+/// A model name as it appears in variable and Tailwind names.
+fn kebab(name: &str) -> String {
+    name.replace('_', "-")
+}
+
+/// Roles that are nested under one name in Tailwind.
+struct Group {
+    /// Empty for top-level colors like `background`.
+    name: &'static str,
+    /// Whether each role also gets shades 50 to 950.
+    ramps: bool,
+    /// Variable names, without `--snowfall-`, and colors.
+    colors: Vec<(String, Color)>,
+}
+
+fn groups(theme: &Theme) -> Vec<Group> {
+    let named = |prefix: &str, colors: Vec<(&str, Color)>| {
+        colors
+            .into_iter()
+            .map(|(name, color)| (format!("{}-{}", prefix, kebab(name)), color))
+            .collect::<Vec<_>>()
+    };
+
+    let group = |name, ramps, colors| Group {
+        name,
+        ramps,
+        colors,
+    };
+
+    vec![
+        group(
+            "",
+            false,
+            vec![
+                ("background".into(), theme.background),
+                ("foreground".into(), theme.foreground),
+                ("secondary-bg".into(), theme.secondary_bg()),
+                ("border".into(), theme.border()),
+                ("doc-comment".into(), theme.doc_comment()),
+            ],
+        ),
+        group("", true, vec![("accent".into(), theme.accent)]),
+        group("syntax", true, named("syntax", theme.tokens.colors())),
+        group(
+            "terminal",
+            false,
+            named("terminal", theme.terminal.colors()),
+        ),
+        group("git", false, named("git", theme.git.colors())),
+        group(
+            "diagnostics",
+            false,
+            named("diagnostics", theme.diagnostics.colors()),
+        ),
+    ]
+}
+
+/// Every custom property of a theme, ramps included, without the
+/// `--snowfall-` prefix.
+pub fn variables(theme: &Theme) -> Vec<(String, Color)> {
+    let mut variables = vec![];
+    for group in groups(theme) {
+        for (name, color) in group.colors {
+            variables.push((name.clone(), color));
+            if group.ramps {
+                for (shade, color) in ramp(color) {
+                    variables.push((format!("{}-{}", name, shade), color));
+                }
+            }
+        }
+    }
+    variables
+}
+
+fn block(css: &mut String, selector: &str, indent: &str, theme: &Theme) {
+    let _ = writeln!(css, "{}{} {{", indent, selector);
+    let scheme = match theme.brightness {
+        Brightness::Dark => "dark",
+        Brightness::Light => "light",
+    };
+    let _ = writeln!(css, "{}  color-scheme: {};", indent, scheme);
+    for (name, color) in variables(theme) {
+        let _ = writeln!(css, "{}  --snowfall-{}: {};", indent, name, color.to_hex());
+    }
+    // Bare channels for `rgb(var(--snowfall-accent-rgb) / 0.5)`, which is how
+    // Tailwind applies opacity modifiers. Translucent colors are composited
+    // over the background first.
+    for (name, color) in variables(theme) {
+        let [red, green, blue] = color.over(theme.background).rgb();
+        let _ = writeln!(
+            css,
+            "{}  --snowfall-{}-rgb: {} {} {};",
+            indent, name, red, green, blue
+        );
+    }
+    let _ = writeln!(css, "{}}}", indent);
+}
+
+/// One stylesheet for the package: each theme under `[data-theme="<slug>"]`,
+/// the first dark theme on `:root`, and the first light theme on `:root` when
+/// the system prefers light and no theme is set.
+pub fn stylesheet(package: &ThemePackage) -> String {
+    let themes = package
+        .themes
+        .iter()
+        .filter(|theme| !theme.is_background_syntax)
+        .collect::<Vec<_>>();
+    let first = |brightness: Brightness| {
+        themes
+            .iter()
+            .position(|theme| theme.brightness == brightness)
+    };
+    let dark = first(Brightness::Dark);
+
+    let mut css = format!(
+        "/* {} by {}, generated by snowfall-zed */\n",
+        package.name, package.author
+    );
+    for (index, theme) in themes.iter().enumerate() {
+        let selector = format!("[data-theme=\"{}\"]", theme.slug());
+        let selector = match Some(index) == dark {
+            true => format!(":root,\n{}", selector),
+            false => selector,
+        };
+        css.push('\n');
+        block(&mut css, &selector, "", theme);
+    }
+    if let Some(light) = first(Brightness::Light) {
+        css.push_str("\n@media (prefers-color-scheme: light) {\n");
+        block(&mut css, ":root:not([data-theme])", "  ", themes[light]);
+        css.push_str("}\n");
+    }
+    css
+}
+
+/// A Tailwind preset whose colors are the stylesheet's channel properties, so
+/// `bg-background`, `text-syntax-keywords` or `border-accent-300` follow
+/// `data-theme` and opacity modifiers like `bg-accent/50` work. Use it
+/// together with the stylesheet. `None` for a package without themes.
+pub fn preset(package: &ThemePackage) -> Option<String> {
+    let theme = package.themes.first()?;
+    let color =
+        |variable: &str| format!("\"rgb(var(--snowfall-{}-rgb) / <alpha-value>)\"", variable);
+    let mut js = format!(
+        "// {} by {}, generated by snowfall-zed. Needs the {}.css custom properties.\n\
+         /** @type {{import('tailwindcss').Config}} */\n\
+         module.exports = {{\n  theme: {{\n    extend: {{\n      colors: {{\n",
+        package.name,
+        package.author,
+        package.name.to_lowercase()
+    );
+
+    for Group {
+        name: group,
+        ramps,
+        colors,
+    } in groups(theme)
+    {
+        let indent = match group {
+            "" => "        ",
+            group => {
+                let _ = writeln!(js, "        {}: {{", group);
+                "          "
+            }
+        };
+        for (variable, _) in colors {
+            let name = variable
+                .strip_prefix(&format!("{}-", group))
+                .unwrap_or(&variable);
+            let _ = match ramps {
+                false => writeln!(js, "{}\"{}\": {},", indent, name, color(&variable)),
+                true => {
+                    let _ = writeln!(js, "{}\"{}\": {{", indent, name);
+                    let _ = writeln!(js, "{}  DEFAULT: {},", indent, color(&variable));
+                    for (shade, _) in SHADES {
+                        let _ = writeln!(
+                            js,
+                            "{}  {}: {},",
+                            indent,
+                            shade,
+                            color(&format!("{}-{}", variable, shade))
+                        );
+                    }
+                    writeln!(js, "{}}},", indent)
+                }
+            };
+        }
+        if !group.is_empty() {
+            js.push_str("        },\n");
+        }
+    }
+
+    js.push_str("      },\n    },\n  },\n};\n");
+    Some(js)
+}
+
+/// Writes `dist/web/<package>.css`.
+pub struct CssExporter;
+
+impl ThemeExporter for CssExporter {
+    fn name(&self) -> &str {
+        "css"
+    }
+
+    fn extension(&self) -> &str {
+        "css"
+    }
+
+    fn export(&self, package: &ThemePackage) -> Vec<OutputFile> {
+        vec![OutputFile {
+            path: Path::new("dist")
+                .join("web")
+                .join(format!("{}.css", package.name.to_lowercase())),
+            contents: stylesheet(package),
+        }]
+    }
+}
+
+/// Writes `dist/web/<package>.preset.js`.
+pub struct TailwindExporter;
+
+impl ThemeExporter for TailwindExporter {
+    fn name(&self) -> &str {
+        "tailwind"
+    }
+
+    fn extension(&self) -> &str {
+        "js"
+    }
+
+    fn export(&self, package: &ThemePackage) -> Vec<OutputFile> {
+        preset(package)
+            .map(|contents| OutputFile {
+                path: Path::new("dist")
+                    .join("web")
+                    .join(format!("{}.preset.js", package.name.to_lowercase())),
+                contents,
+            })
+            .into_iter()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ramps_keep_the_color_and_get_darker() {
+        let accent = Color::from_hex("#84c4df");
+        let ramp = ramp(accent);
+
+        assert_eq!(ramp.len(), 11);
+        assert!(ramp.iter().any(|(_, color)| *color == accent));
+        assert!(ramp
+            .windows(2)
+            .all(|pair| pair[0].1.to_oklch().0 > pair[1].1.to_oklch().0));
+    }
+
+    #[test]
+    fn scopes_themes_by_data_theme_and_color_scheme() {
+        let css = stylesheet(&crate::snowfall());
+
+        assert!(css.contains(":root,\n[data-theme=\"snowfall_dark\"] {\n  color-scheme: dark;\n"));
+        assert!(css.contains("[data-theme=\"snowfall_light_colorblind\"] {"));
+        assert!(!css.contains("background_syntax"));
+        assert!(css.contains("  --snowfall-accent: #84c4df;\n"));
+        assert!(css.contains("  --snowfall-accent-rgb: 132 196 223;\n"));
+        assert!(css.contains("  --snowfall-syntax-keywords-950: "));
+        assert!(css.contains(
+            "@media (prefers-color-scheme: light) {\n  :root:not([data-theme]) {\n    color-scheme: light;\n"
+        ));
+    }
+
+    #[test]
+    fn preset_uses_only_defined_properties() {
+        let package = crate::snowfall();
+        let css = stylesheet(&package);
+        let js = preset(&package).unwrap();
+
+        assert!(js.contains("        syntax: {\n          \"brackets\": {\n"));
+        assert!(js.contains(
+            "\"keywords\": {\n            DEFAULT: \"rgb(var(--snowfall-syntax-keywords-rgb) / <alpha-value>)\","
+        ));
+        assert!(js.contains(
+            "\"bright-red\": \"rgb(var(--snowfall-terminal-bright-red-rgb) / <alpha-value>)\""
+        ));
+        for reference in js.split("var(").skip(1) {
+            let name = &reference[..reference.find(')').unwrap()];
+            assert!(css.contains(&format!("  {}: ", name)), "{}", name);
+        }
+    }
+
+    #[test]
+    fn empty_packages_have_no_preset() {
+        let package = ThemePackage {
+            name: "Empty".into(),
+            author: "nobody".into(),
+            themes: vec![],
+        };
+
+        assert!(TailwindExporter.export(&package).is_empty());
+    }
+}
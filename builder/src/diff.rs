@@ -0,0 +1,326 @@
+use std::fmt::{Display, Write};
+
+use serde_json::Value;
+
+use crate::{color::Color, show};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Subsystem {
+    Syntax,
+    Git,
+    Diagnostics,
+    Terminal,
+    Surfaces,
+}
+
+impl Subsystem {
+    pub fn of(key: &str) -> Self {
+        let root = key.split(['.', '[']).next().unwrap_or_default();
+        match root {
+            "syntax" => Subsystem::Syntax,
+            "terminal" => Subsystem::Terminal,
+            "version_control" | "created" | "modified" | "deleted" | "conflict" | "renamed"
+            | "ignored" => Subsystem::Git,
+            "error" | "warning" | "info" | "hint" | "success" | "unreachable" => {
+                Subsystem::Diagnostics
+            }
+            _ => Subsystem::Surfaces,
+        }
+    }
+}
+
+impl Display for Subsystem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Subsystem::Syntax => "syntax",
+            Subsystem::Git => "git",
+            Subsystem::Diagnostics => "diagnostics",
+            Subsystem::Terminal => "terminal",
+            Subsystem::Surfaces => "surfaces",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// One style key whose color differs between two builds.
+pub struct Change {
+    pub key: String,
+    pub subsystem: Subsystem,
+    pub before: Option<Color>,
+    pub after: Option<Color>,
+    /// The values as written, before and after, which may not parse as
+    /// colors, e.g. `#ff00000`.
+    pub values: (Option<String>, Option<String>),
+    /// Contrast against the editor background (or foreground, for
+    /// backgrounds), before and after.
+    pub contrast: (Option<f32>, Option<f32>),
+}
+
+impl Change {
+    pub fn delta_e(&self) -> Option<f32> {
+        Some(self.before?.delta_e(self.after?))
+    }
+}
+
+/// Every color in a theme style, keyed like `editor.background`,
+/// `syntax.keyword.color` or `players[0].cursor`.
+pub fn colors(style: &Value) -> Vec<(String, Color)> {
+    values(style)
+        .into_iter()
+        .filter_map(|(key, hex)| Some((key, Color::parse(&hex)?)))
+        .collect()
+}
+
+/// Every `#` value in a theme style as written, including malformed ones.
+fn values(style: &Value) -> Vec<(String, String)> {
+    fn walk(path: String, value: &Value, colors: &mut Vec<(String, String)>) {
+        match value {
+            Value::Object(map) => {
+                for (key, value) in map {
+                    let path = match path.as_str() {
+                        "" => key.clone(),
+                        path => format!("{}.{}", path, key),
+                    };
+                    walk(path, value, colors);
+                }
+            }
+            Value::Array(values) => {
+                for (index, value) in values.iter().enumerate() {
+                    walk(format!("{}[{}]", path, index), value, colors);
+                }
+            }
+            Value::String(hex) if hex.starts_with('#') => colors.push((path, hex.clone())),
+            _ => {}
+        }
+    }
+
+    let mut colors = vec![];
+    walk(String::new(), style, &mut colors);
+    colors
+}
+
+/// How readable `key` is on screen: translucent colors are composited over
+/// the editor background first.
+pub fn contrast(style: &[(String, Color)], key: &str, color: Color) -> Option<f32> {
+    let find = |name: &str| {
+        style
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, color)| *color)
+    };
+    let background = find("editor.background")?;
+    let against = match key.contains("background") {
+        true => find("editor.foreground")?.over(background),
+        false => background,
+    };
+
+    Some(color.over(background).contrast(against))
+}
+
+/// Changed colors between two versions of the same theme style, grouped by
+/// subsystem. Values that aren't valid colors are compared as text.
+pub fn changes(old: &Value, new: &Value) -> Vec<Change> {
+    let (old_values, new_values) = (values(old), values(new));
+    let (old, new) = (colors(old), colors(new));
+    let find = |values: &[(String, String)], key: &str| {
+        values
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value.clone())
+    };
+
+    let mut keys = old_values
+        .iter()
+        .chain(&new_values)
+        .map(|(key, _)| key.clone())
+        .collect::<Vec<_>>();
+    keys.sort_by_key(|key| (Subsystem::of(key), key.clone()));
+    keys.dedup();
+
+    let mut changes = vec![];
+    for key in keys {
+        let values = (find(&old_values, &key), find(&new_values, &key));
+        let parse = |value: &Option<String>| value.as_deref().and_then(Color::parse);
+        let (before, after) = (parse(&values.0), parse(&values.1));
+        let same = match (before, after) {
+            (Some(before), Some(after)) => before == after,
+            _ => values.0 == values.1,
+        };
+        if same {
+            continue;
+        }
+        changes.push(Change {
+            subsystem: Subsystem::of(&key),
+            contrast: (
+                before.and_then(|color| contrast(&old, &key, color)),
+                after.and_then(|color| contrast(&new, &key, color)),
+            ),
+            key,
+            before,
+            after,
+            values,
+        });
+    }
+
+    changes
+}
+
+/// A 24-bit ANSI color block, with the hex value next to it. Values that
+/// aren't colors are shown as written.
+fn swatch(color: Option<Color>, value: Option<&str>, background: Color) -> String {
+    match (color, value) {
+        (Some(color), _) => {
+            format!(
+                "{}    \x1b[0m {:<9}",
+                show::background(color.over(background)),
+                color.to_hex()
+            )
+        }
+        (None, Some(value)) => format!("{:<4} {:<9}", "", value),
+        (None, None) => format!("{:<4} {:<9}", "", "(none)"),
+    }
+}
+
+fn themes(document: &Value) -> Vec<(String, &Value)> {
+    document["themes"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|theme| {
+            (
+                theme["name"].as_str().unwrap_or("Untitled").to_string(),
+                &theme["style"],
+            )
+        })
+        .collect()
+}
+
+/// Human readable diff between two theme families, matching themes by name.
+pub fn report(old: &str, new: &str) -> Result<String, String> {
+    let old: Value = serde_json::from_str(old).map_err(|error| error.to_string())?;
+    let new: Value = serde_json::from_str(new).map_err(|error| error.to_string())?;
+    let (old, new) = (themes(&old), themes(&new));
+    let mut report = String::new();
+
+    for (name, _) in old
+        .iter()
+        .filter(|(name, _)| !new.iter().any(|(n, _)| n == name))
+    {
+        let _ = writeln!(report, "{}\n  removed\n", name);
+    }
+    for (name, style) in &new {
+        let Some((_, old_style)) = old.iter().find(|(n, _)| n == name) else {
+            let _ = writeln!(report, "{}\n  added\n", name);
+            continue;
+        };
+        let changes = changes(old_style, style);
+        if changes.is_empty() {
+            continue;
+        }
+
+        let background = colors(style)
+            .into_iter()
+            .find(|(key, _)| key == "editor.background")
+            .map_or(Color::from_hex("#000000"), |(_, color)| color);
+        let width = changes.iter().map(|change| change.key.len()).max().unwrap();
+        let _ = writeln!(report, "{}", name);
+
+        let mut subsystem = None;
+        for change in &changes {
+            if subsystem != Some(change.subsystem) {
+                subsystem = Some(change.subsystem);
+                let _ = writeln!(report, "  {}", change.subsystem);
+            }
+            let _ = write!(
+                report,
+                "    {:<width$}  {} -> {}",
+                change.key,
+                swatch(change.before, change.values.0.as_deref(), background),
+                swatch(change.after, change.values.1.as_deref(), background),
+            );
+            if let Some(delta_e) = change.delta_e() {
+                let _ = write!(report, "  ΔE {:>5.1}", delta_e);
+            }
+            if let (Some(before), Some(after)) = change.contrast {
+                let _ = write!(report, "  contrast {:.2} -> {:.2}", before, after);
+            }
+            report.push('\n');
+        }
+        report.push('\n');
+    }
+
+    if report.is_empty() {
+        report.push_str("no changes\n");
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::ZedExporter;
+
+    #[test]
+    fn groups_changes_by_subsystem() {
+        let package = crate::snowfall();
+        let old: Value = serde_json::from_str(&ZedExporter::json(&package)).unwrap();
+        let mut new = old.clone();
+        let style = &mut new["themes"][0]["style"];
+        style["syntax"]["keyword"]["color"] = "#ff0000".into();
+        style["terminal.ansi.red"] = "#ff0000".into();
+
+        let changes = changes(&old["themes"][0]["style"], &new["themes"][0]["style"]);
+        let keys = changes
+            .iter()
+            .map(|change| (change.subsystem, change.key.as_str()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            keys,
+            [
+                (Subsystem::Syntax, "syntax.keyword.color"),
+                (Subsystem::Terminal, "terminal.ansi.red")
+            ]
+        );
+        assert!(changes[0].delta_e().unwrap() > 10.0);
+        assert!(changes[0].contrast.1.is_some());
+    }
+
+    #[test]
+    fn reports_malformed_values_as_text() {
+        let old = serde_json::json!({ "info.border": "#ff00000", "hint": "#ff0000" });
+        let new = serde_json::json!({ "info.border": "#ff0000", "hint": "#ff00zz" });
+
+        let changes = changes(&old, &new);
+        let values = changes
+            .iter()
+            .map(|change| (change.key.as_str(), change.values.clone()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            values,
+            [
+                ("hint", (Some("#ff0000".into()), Some("#ff00zz".into()))),
+                (
+                    "info.border",
+                    (Some("#ff00000".into()), Some("#ff0000".into()))
+                ),
+            ]
+        );
+        assert_eq!(changes[1].before, None);
+        assert!(report(
+            &serde_json::json!({ "themes": [{ "name": "A", "style": old }] }).to_string(),
+            &serde_json::json!({ "themes": [{ "name": "A", "style": new }] }).to_string(),
+        )
+        .unwrap()
+        .contains("#ff00000"));
+    }
+
+    #[test]
+    fn identical_builds_have_no_changes() {
+        let json = ZedExporter::json(&crate::snowfall());
+
+        assert_eq!(report(&json, &json).unwrap(), "no changes\n");
+    }
+}
@@ -0,0 +1,497 @@
+use std::{fmt::Write, path::Path};
+
+use serde_json::{json, Value};
+
+use crate::{
+    builder::{SyntaxStyle, SyntaxTheme},
+    color::Color,
+    export::{OutputFile, ThemeExporter},
+    theme::{Brightness, Theme, ThemePackage},
+};
+
+/// Pygments token types and the Zed capture whose style they take. Chroma
+/// uses the same types with the dots removed (`LiteralStringEscape`).
+const PYGMENTS: [(&str, &str); 31] = [
+    ("Comment", "comment"),
+    ("Comment.Preproc", "attribute"),
+    ("Comment.Special", "comment.doc"),
+    ("Keyword", "keyword"),
+    ("Keyword.Constant", "boolean"),
+    ("Keyword.Type", "type"),
+    ("Name.Attribute", "attribute"),
+    ("Name.Builtin", "function"),
+    ("Name.Class", "type"),
+    ("Name.Constant", "constant"),
+    ("Name.Decorator", "attribute"),
+    ("Name.Function", "function"),
+    ("Name.Label", "label"),
+    ("Name.Property", "property"),
+    ("Name.Tag", "tag"),
+    ("Name.Variable", "variable"),
+    ("Literal.String", "string"),
+    ("Literal.String.Char", "character"),
+    ("Literal.String.Doc", "comment.doc"),
+    ("Literal.String.Escape", "string.escape"),
+    ("Literal.String.Regex", "string.regex"),
+    ("Literal.String.Symbol", "string.special.symbol"),
+    ("Literal.Number", "number"),
+    ("Operator", "operator"),
+    ("Punctuation", "punctuation"),
+    ("Generic.Heading", "title"),
+    ("Generic.Subheading", "title"),
+    ("Generic.Emph", "emphasis"),
+    ("Generic.Strong", "emphasis.strong"),
+    ("Generic.Inserted", "diff.plus"),
+    ("Generic.Deleted", "diff.minus"),
+];
+
+/// highlight.js classes (without `hljs-`) per Zed capture.
+const HIGHLIGHT_JS: [(&str, &[&str]); 25] = [
+    ("comment", &["comment", "quote"]),
+    ("comment.doc", &["doctag"]),
+    ("keyword", &["keyword", "selector-tag"]),
+    ("boolean", &["literal"]),
+    ("number", &["number"]),
+    ("string", &["string"]),
+    ("string.escape", &["char.escape_"]),
+    ("string.regex", &["regexp"]),
+    ("string.special.symbol", &["symbol"]),
+    ("function", &["title.function_", "built_in"]),
+    (
+        "type",
+        &["type", "title.class_", "title.class_.inherited__"],
+    ),
+    (
+        "attribute",
+        &["attribute", "meta", "selector-attr", "selector-pseudo"],
+    ),
+    ("property", &["attr", "property"]),
+    ("variable", &["variable", "template-variable"]),
+    ("variable.special", &["variable.language_"]),
+    ("tag", &["tag", "name", "selector-class", "selector-id"]),
+    ("operator", &["operator"]),
+    ("punctuation", &["punctuation"]),
+    ("title", &["section", "title"]),
+    ("emphasis", &["emphasis"]),
+    ("emphasis.strong", &["strong"]),
+    ("link_uri", &["link"]),
+    ("text.literal", &["code"]),
+    ("diff.plus", &["addition"]),
+    ("diff.minus", &["deletion"]),
+];
+
+/// TextMate scopes per Zed capture, for Shiki.
+const TEXTMATE: [(&str, &[&str]); 29] = [
+    ("comment", &["comment", "punctuation.definition.comment"]),
+    (
+        "comment.doc",
+        &["comment.block.documentation", "comment.line.documentation"],
+    ),
+    ("keyword", &["keyword", "storage.type", "storage.modifier"]),
+    ("operator", &["keyword.operator"]),
+    ("boolean", &["constant.language"]),
+    ("number", &["constant.numeric"]),
+    ("constant", &["constant.other", "variable.other.constant"]),
+    ("string", &["string"]),
+    ("character", &["constant.character"]),
+    ("string.escape", &["constant.character.escape"]),
+    ("string.regex", &["string.regexp"]),
+    ("string.special.symbol", &["constant.other.symbol"]),
+    (
+        "function",
+        &[
+            "entity.name.function",
+            "support.function",
+            "meta.function-call",
+        ],
+    ),
+    (
+        "type",
+        &[
+            "entity.name.type",
+            "entity.name.class",
+            "support.type",
+            "support.class",
+        ],
+    ),
+    (
+        "attribute",
+        &["entity.other.attribute-name", "meta.attribute"],
+    ),
+    (
+        "property",
+        &[
+            "variable.other.property",
+            "variable.other.object.property",
+            "support.type.property-name",
+        ],
+    ),
+    ("variable", &["variable"]),
+    ("variable.special", &["variable.language"]),
+    ("tag", &["entity.name.tag"]),
+    ("label", &["entity.name.label"]),
+    ("punctuation", &["punctuation"]),
+    (
+        "punctuation.bracket",
+        &["punctuation.bracket", "meta.brace", "punctuation.section"],
+    ),
+    ("title", &["markup.heading", "entity.name.section"]),
+    ("emphasis", &["markup.italic"]),
+    ("emphasis.strong", &["markup.bold"]),
+    ("link_uri", &["markup.underline.link"]),
+    ("link_text", &["string.other.link"]),
+    ("diff.plus", &["markup.inserted"]),
+    ("diff.minus", &["markup.deleted"]),
+];
+
+/// The style of a capture, plus `diff.plus`, `diff.minus` and `diff.delta`
+/// for inserted, deleted and changed lines, which Zed colors from git rather
+/// than syntax, and `operator`, which Zed leaves to the foreground.
+pub fn style(theme: &Theme, capture: &str) -> SyntaxStyle {
+    let plain = |color: Color| SyntaxStyle {
+        color,
+        background: None,
+        italic: false,
+        bold: false,
+    };
+    match capture {
+        "diff.plus" => plain(theme.git.added),
+        "diff.minus" => plain(theme.git.removed),
+        "diff.delta" => plain(theme.git.modified),
+        "operator" => plain(theme.tokens.operators),
+        capture => theme.highlight(capture),
+    }
+}
+
+/// A Pygments/Chroma style string. Neither supports alpha, so colors are
+/// composited over the background first.
+fn pygments_style(theme: &Theme, style: &SyntaxStyle) -> String {
+    let mut parts = vec![];
+    if style.italic {
+        parts.push("italic".to_string());
+    }
+    if style.bold {
+        parts.push("bold".to_string());
+    }
+    parts.push(style.color.over(theme.background).to_hex());
+    if let Some(background) = style.background {
+        parts.push(format!("bg:{}", background.over(theme.background).to_hex()));
+    }
+    parts.join(" ")
+}
+
+/// `snowfall_dark` to `SnowfallDarkStyle`.
+fn class_name(theme: &Theme) -> String {
+    let mut name = theme
+        .slug()
+        .split('_')
+        .map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .map_or(String::new(), |first| first.to_uppercase().collect())
+                + chars.as_str()
+        })
+        .collect::<String>();
+    name.push_str("Style");
+    name
+}
+
+/// A Pygments style class, for Sphinx and MkDocs (`pygments_style`).
+pub fn pygments(theme: &Theme) -> String {
+    let mut python = format!(
+        "\"\"\"{} for Pygments, generated by snowfall-zed.\"\"\"\n\n\
+         from pygments.style import Style\n\
+         from pygments.token import Token\n\n\n\
+         class {}(Style):\n    name = \"{}\"\n",
+        theme.name,
+        class_name(theme),
+        theme.slug(),
+    );
+    let _ = writeln!(
+        python,
+        "    background_color = \"{}\"",
+        theme.background.to_hex()
+    );
+    let _ = writeln!(
+        python,
+        "    highlight_color = \"{}\"",
+        theme
+            .accent
+            .with_opacity(0.2)
+            .over(theme.background)
+            .to_hex()
+    );
+    let _ = writeln!(
+        python,
+        "    line_number_color = \"{}\"",
+        theme
+            .foreground
+            .with_opacity(0.4)
+            .over(theme.background)
+            .to_hex()
+    );
+    python.push_str("    styles = {\n");
+    let _ = writeln!(python, "        Token: \"{}\",", theme.foreground.to_hex());
+    for (token, capture) in PYGMENTS {
+        let _ = writeln!(
+            python,
+            "        Token.{}: \"{}\",",
+            token,
+            pygments_style(theme, &style(theme, capture))
+        );
+    }
+    python.push_str("    }\n");
+    python
+}
+
+/// A Chroma style, for Hugo (`markup.highlight.style`).
+pub fn chroma(theme: &Theme) -> String {
+    let mut xml = format!("<style name=\"{}\">\n", theme.slug());
+    let entry = |xml: &mut String, kind: &str, style: String| {
+        let _ = writeln!(xml, "  <entry type=\"{}\" style=\"{}\"/>", kind, style);
+    };
+    entry(
+        &mut xml,
+        "Background",
+        format!(
+            "{} bg:{}",
+            theme.foreground.to_hex(),
+            theme.background.to_hex()
+        ),
+    );
+    entry(
+        &mut xml,
+        "LineHighlight",
+        format!(
+            "bg:{}",
+            theme
+                .accent
+                .with_opacity(0.2)
+                .over(theme.background)
+                .to_hex()
+        ),
+    );
+    entry(
+        &mut xml,
+        "LineNumbers",
+        theme
+            .foreground
+            .with_opacity(0.4)
+            .over(theme.background)
+            .to_hex(),
+    );
+    for (token, capture) in PYGMENTS {
+        entry(
+            &mut xml,
+            &token.replace('.', ""),
+            pygments_style(theme, &style(theme, capture)),
+        );
+    }
+    xml.push_str("</style>\n");
+    xml
+}
+
+/// A highlight.js theme stylesheet.
+pub fn highlight_js(theme: &Theme) -> String {
+    let mut css = format!(
+        "/* {} for highlight.js, generated by snowfall-zed */\n\n\
+         .hljs {{\n  color: {};\n  background: {};\n}}\n",
+        theme.name,
+        theme.foreground.to_css(),
+        theme.background.to_css(),
+    );
+    for (capture, classes) in HIGHLIGHT_JS {
+        let style = style(theme, capture);
+        let selectors = classes
+            .iter()
+            .map(|class| format!(".hljs-{}", class))
+            .collect::<Vec<_>>()
+            .join(",\n");
+        let _ = write!(
+            css,
+            "\n{} {{\n  color: {};\n",
+            selectors,
+            style.color.to_css()
+        );
+        if let Some(background) = style.background {
+            let _ = writeln!(css, "  background: {};", background.to_css());
+        }
+        if style.italic {
+            css.push_str("  font-style: italic;\n");
+        }
+        if style.bold {
+            css.push_str("  font-weight: bold;\n");
+        }
+        css.push_str("}\n");
+    }
+    css
+}
+
+/// A TextMate theme in the VS Code layout, which Shiki loads as is.
+pub fn shiki(theme: &Theme) -> String {
+    let token_colors = TEXTMATE
+        .iter()
+        .map(|(capture, scopes)| {
+            let style = style(theme, capture);
+            let mut font_style = vec![];
+            if style.italic {
+                font_style.push("italic");
+            }
+            if style.bold {
+                font_style.push("bold");
+            }
+            let mut settings = json!({
+                "foreground": style.color,
+                "fontStyle": font_style.join(" "),
+            });
+            if let Some(background) = style.background {
+                settings["background"] = json!(background);
+            }
+            json!({ "name": capture, "scope": scopes, "settings": settings })
+        })
+        .collect::<Vec<Value>>();
+
+    let document = json!({
+        "name": theme.slug(),
+        "displayName": theme.name,
+        "type": match theme.brightness {
+            Brightness::Dark => "dark",
+            Brightness::Light => "light",
+        },
+        "colors": {
+            "editor.background": theme.background,
+            "editor.foreground": theme.foreground,
+            "editor.selectionBackground": theme.accent.with_opacity(0.2),
+            "editorLineNumber.foreground": theme.foreground.with_opacity(0.4),
+        },
+        "tokenColors": token_colors,
+    });
+    serde_json::to_string_pretty(&document).unwrap() + "\n"
+}
+
+/// Writes one documentation highlighter style per theme to
+/// `dist/<name>/<theme>.<extension>`.
+pub struct DocsExporter {
+    name: &'static str,
+    extension: &'static str,
+    render: fn(&Theme) -> String,
+}
+
+impl DocsExporter {
+    /// Every documentation highlighter: Pygments, Chroma, highlight.js and
+    /// Shiki.
+    pub fn all() -> Vec<Self> {
+        vec![
+            Self {
+                name: "pygments",
+                extension: "py",
+                render: pygments,
+            },
+            Self {
+                name: "chroma",
+                extension: "xml",
+                render: chroma,
+            },
+            Self {
+                name: "hljs",
+                extension: "css",
+                render: highlight_js,
+            },
+            Self {
+                name: "shiki",
+                extension: "json",
+                render: shiki,
+            },
+        ]
+    }
+}
+
+impl ThemeExporter for DocsExporter {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn extension(&self) -> &str {
+        self.extension
+    }
+
+    fn export(&self, package: &ThemePackage) -> Vec<OutputFile> {
+        package
+            .themes
+            .iter()
+            .map(|theme| OutputFile {
+                path: Path::new("dist").join(self.name).join(format!(
+                    "{}.{}",
+                    theme.slug(),
+                    self.extension
+                )),
+                contents: (self.render)(theme),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_tokens_with_font_styles() {
+        let package = crate::snowfall();
+        let theme = &package.themes[0];
+        let keywords = theme.tokens.keywords.to_hex();
+        let types = format!("bold {}", theme.tokens.types.to_hex());
+
+        assert!(pygments(theme).contains(&format!("        Token.Keyword: \"{}\",\n", keywords)));
+        assert!(pygments(theme).contains(&format!("        Token.Keyword.Type: \"{}\",\n", types)));
+        assert!(pygments(theme).contains("class SnowfallDarkStyle(Style):"));
+        assert!(pygments(theme).contains(&format!(
+            "        Token.Operator: \"{}\",\n",
+            theme.tokens.operators.to_hex()
+        )));
+        assert!(chroma(theme).contains(&format!(
+            "  <entry type=\"LiteralStringEscape\" style=\"{}\"/>",
+            theme.tokens.constants.to_hex()
+        )));
+        assert!(highlight_js(theme).contains(&format!(
+            ".hljs-type,\n.hljs-title.class_,\n.hljs-title.class_.inherited__ {{\n  color: {};\n  font-weight: bold;\n}}",
+            theme.tokens.types.to_hex()
+        )));
+
+        let background = &package.themes[1];
+        assert!(pygments(background).contains(&format!(
+            "Token.Literal.String: \"{} bg:{}\"",
+            background.tokens.strings.to_hex(),
+            background
+                .tokens
+                .strings
+                .with_opacity(0.2)
+                .over(background.background)
+                .to_hex()
+        )));
+    }
+
+    #[test]
+    fn names_style_classes_after_non_ascii_themes() {
+        let theme = Theme {
+            name: "Snowfall Ämber".into(),
+            ..crate::snowfall().themes[0].clone()
+        };
+
+        assert!(pygments(&theme).contains("class SnowfallÄmberStyle(Style):"));
+    }
+
+    #[test]
+    fn shiki_themes_read_back_as_textmate_rules() {
+        for theme in crate::snowfall().themes {
+            let imported = crate::import::vscode(&shiki(&theme)).unwrap();
+
+            assert_eq!(imported.background, Some(theme.background));
+            assert_eq!(imported.tokens["keywords"], theme.tokens.keywords);
+            assert_eq!(imported.tokens["strings"], theme.tokens.strings);
+            assert_eq!(imported.tokens["functions"], theme.tokens.functions);
+            assert_eq!(imported.tokens["comment"], theme.tokens.comment);
+        }
+    }
+}
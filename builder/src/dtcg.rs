@@ -0,0 +1,478 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::Path,
+};
+
+use serde_json::{json, Map, Value};
+
+use crate::{
+    color::Color,
+    export::{OutputFile, ThemeExporter},
+    expr::{self, Expr},
+    import::ImportedPalette,
+    theme::{Brightness, Theme, ThemePackage},
+};
+
+/// Key of our entries in `$extensions`.
+const EXTENSION: &str = "snowfall";
+
+const TOKENS: [&str; 9] = [
+    "brackets",
+    "comment",
+    "strings",
+    "types",
+    "functions",
+    "properties",
+    "keywords",
+    "constants",
+    "operators",
+];
+const TERMINAL: [&str; 16] = [
+    "black",
+    "red",
+    "green",
+    "yellow",
+    "blue",
+    "magenta",
+    "cyan",
+    "white",
+    "bright_black",
+    "bright_red",
+    "bright_green",
+    "bright_yellow",
+    "bright_blue",
+    "bright_magenta",
+    "bright_cyan",
+    "bright_white",
+];
+const GIT: [&str; 3] = ["added", "modified", "removed"];
+const DIAGNOSTICS: [&str; 3] = ["error", "warning", "info"];
+
+/// Token path of a palette definition (see
+/// [`ThemeCreatePayload::palette`](crate::theme::ThemeCreatePayload::palette)):
+/// `tokens.comment` is `syntax.comment`, `border` is `ui.border`, a named
+/// color `frost` is `color.frost`.
+fn token_path(definition: &str, theme: &Theme) -> String {
+    match definition.split_once('.') {
+        Some(("tokens", role)) => format!("syntax.{}", role),
+        Some(_) => definition.to_string(),
+        None if theme.colors.contains_key(definition) => format!("color.{}", definition),
+        None if definition == "doc_comment" => "syntax.doc_comment".into(),
+        None => format!("ui.{}", definition),
+    }
+}
+
+/// The inverse of [`token_path`], for aliases found while importing. Tokens
+/// outside the palette, e.g. `brand.primary`, have no definition.
+fn definition(path: &str) -> Option<String> {
+    match path.split_once('.')? {
+        ("color", name) => Some(name.into()),
+        ("ui", name @ ("accent" | "background" | "foreground" | "secondary_bg" | "border"))
+        | ("syntax", name @ "doc_comment") => Some(name.into()),
+        ("syntax", role) if TOKENS.contains(&role) => Some(format!("tokens.{}", role)),
+        ("terminal", role) if TERMINAL.contains(&role) => Some(path.into()),
+        ("git", role) if GIT.contains(&role) => Some(path.into()),
+        ("diagnostics", role) if DIAGNOSTICS.contains(&role) => Some(path.into()),
+        _ => None,
+    }
+}
+
+/// One theme as a Design Tokens Community Group file: named colors in
+/// `color`, and the roles in `ui`, `syntax`, `terminal`, `git` and
+/// `diagnostics`. Roles set from a named color are aliases (`{color.frost}`),
+/// derived roles are aliases when their expression is a plain reference, and
+/// otherwise carry the expression in `$extensions`.
+pub fn export(theme: &Theme, author: &str) -> String {
+    let payload = theme.payload();
+    let palette = payload.palette();
+    let references = payload.references().into_iter().collect::<HashMap<_, _>>();
+
+    let token = |role: String, color: Color| match references.get(&role) {
+        Some(name) => json!({ "$value": format!("{{color.{}}}", name) }),
+        None => json!({ "$value": color }),
+    };
+    let derived = |expression: &str, color: Color| {
+        let reference = match expr::parse(expression) {
+            Ok(expr::Node {
+                expr: Expr::Name(name),
+                ..
+            }) => palette.lookup(&name).ok(),
+            _ => None,
+        };
+        match reference {
+            Some(definition) => {
+                json!({ "$value": format!("{{{}}}", token_path(definition, theme)) })
+            }
+            None => json!({
+                "$value": color,
+                "$extensions": { EXTENSION: { "expression": expression } },
+            }),
+        }
+    };
+    let group = |prefix: &str, colors: Vec<(&str, Color)>| {
+        let mut group = Map::new();
+        group.insert("$type".into(), json!("color"));
+        for (name, color) in colors {
+            let role = match prefix {
+                "" => name.to_string(),
+                prefix => format!("{}.{}", prefix, name),
+            };
+            group.insert(name.into(), token(role, color));
+        }
+        group
+    };
+
+    let mut colors = Map::new();
+    colors.insert("$type".into(), json!("color"));
+    for (name, color) in &theme.colors {
+        colors.insert(name.clone(), json!({ "$value": color }));
+    }
+
+    let mut ui = group(
+        "",
+        vec![
+            ("accent", theme.accent),
+            ("background", theme.background),
+            ("foreground", theme.foreground),
+        ],
+    );
+    ui.insert(
+        "secondary_bg".into(),
+        derived(&theme.derived.secondary_bg, theme.secondary_bg()),
+    );
+    ui.insert(
+        "border".into(),
+        derived(&theme.derived.border, theme.border()),
+    );
+
+    let mut syntax = group("tokens", theme.tokens.colors());
+    syntax.insert(
+        "doc_comment".into(),
+        derived(&theme.derived.doc_comment, theme.doc_comment()),
+    );
+
+    let document = json!({
+        "$description": format!("{} by {}", theme.name, author),
+        "$extensions": {
+            EXTENSION: {
+                "name": theme.name,
+                "appearance": match theme.brightness {
+                    Brightness::Dark => "dark",
+                    Brightness::Light => "light",
+                },
+            },
+        },
+        "color": colors,
+        "ui": ui,
+        "syntax": syntax,
+        "terminal": group("terminal", theme.terminal.colors()),
+        "git": group("git", theme.git.colors()),
+        "diagnostics": group("diagnostics", theme.diagnostics.colors()),
+    });
+    serde_json::to_string_pretty(&document).unwrap() + "\n"
+}
+
+/// Tokens by path, with `$type` inherited from their groups. Only color
+/// tokens (or untyped ones) are kept.
+fn collect<'a>(
+    value: &'a Value,
+    path: &str,
+    kind: Option<&'a str>,
+    tokens: &mut BTreeMap<String, &'a Value>,
+) {
+    let Some(object) = value.as_object() else {
+        return;
+    };
+    let kind = object.get("$type").and_then(Value::as_str).or(kind);
+
+    if object.contains_key("$value") {
+        if kind.is_none_or(|kind| kind == "color") {
+            tokens.insert(path.to_string(), value);
+        }
+        return;
+    }
+    for (key, child) in object {
+        if !key.starts_with('$') {
+            let path = match path {
+                "" => key.clone(),
+                path => format!("{}.{}", path, key),
+            };
+            collect(child, &path, kind, tokens);
+        }
+    }
+}
+
+/// `{syntax.keywords}` to `syntax.keywords`.
+fn alias(value: &Value) -> Option<&str> {
+    value.as_str()?.strip_prefix('{')?.strip_suffix('}')
+}
+
+/// A color value: a hex string, or the newer object form with a `hex` or
+/// sRGB `components` and `alpha`.
+fn color(value: &Value) -> Option<Color> {
+    if let Some(hex) = value.as_str().or(value.get("hex").and_then(Value::as_str)) {
+        let color = Color::parse(hex)?;
+        return match value.get("alpha").and_then(Value::as_f64) {
+            Some(alpha) => Some(color.with_opacity(alpha as f32)),
+            None => Some(color),
+        };
+    }
+
+    if value.get("colorSpace")?.as_str()? != "srgb" {
+        return None;
+    }
+    let channel = |value: &Value| Some((value.as_f64()?.clamp(0.0, 1.0) * 255.0).round() as u8);
+    let components = value.get("components")?.as_array()?;
+    let [red, green, blue] = [0, 1, 2].map(|index| components.get(index).and_then(channel));
+    let alpha = value.get("alpha").map_or(Some(255), channel)?;
+    Color::parse(&format!(
+        "{:02x}{:02x}{:02x}{:02x}",
+        red?, green?, blue?, alpha
+    ))
+}
+
+/// The color a token ends up with, following aliases.
+fn resolve(
+    path: &str,
+    tokens: &BTreeMap<String, &Value>,
+    stack: &mut Vec<String>,
+) -> Result<Color, String> {
+    let value = &tokens[path]["$value"];
+    let Some(target) = alias(value) else {
+        return color(value).ok_or_else(|| format!("{}: {} is not a color", path, value));
+    };
+
+    if stack.iter().any(|visited| visited == target) {
+        stack.push(target.to_string());
+        return Err(format!("Alias cycle: {}", stack.join(" -> ")));
+    }
+    if !tokens.contains_key(target) {
+        return Err(format!("{}: unknown token {{{}}}", path, target));
+    }
+    stack.push(target.to_string());
+    let color = resolve(target, tokens, stack);
+    stack.pop();
+    color
+}
+
+/// Reads a DTCG file laid out like [`export`] writes it. Tokens outside the
+/// known groups are ignored, and missing roles are left for
+/// [`ImportedPalette::complete`].
+pub fn import(source: &str) -> Result<ImportedPalette, String> {
+    let document: Value = serde_json::from_str(source).map_err(|error| error.to_string())?;
+    let mut tokens = BTreeMap::new();
+    collect(&document, "", None, &mut tokens);
+
+    let extension = &document["$extensions"][EXTENSION];
+    let mut imported = ImportedPalette {
+        name: extension["name"].as_str().map(String::from),
+        brightness: match extension["appearance"].as_str() {
+            Some("dark") => Some(Brightness::Dark),
+            Some("light") => Some(Brightness::Light),
+            _ => None,
+        },
+        ..Default::default()
+    };
+
+    for (path, token) in &tokens {
+        // Only palette tokens are read; anything else, like an untyped
+        // `spacing.small`, may not even be a color.
+        if definition(path).is_none() {
+            continue;
+        }
+        let color = resolve(path, &tokens, &mut vec![path.clone()])?;
+        // Derived roles keep their expression, or the reference they alias.
+        let derived = || match token["$extensions"][EXTENSION]["expression"].as_str() {
+            Some(expression) => expression.to_string(),
+            None => alias(&token["$value"])
+                .and_then(definition)
+                .unwrap_or_else(|| color.to_hex()),
+        };
+        let role =
+            |roles: &[&'static str], name: &str| roles.iter().find(|role| **role == name).copied();
+        // Roles aliasing a named color remember it, like `accent = "frost"`
+        // in a spec. Derived roles keep theirs in the expression instead.
+        if let (Some(key), Some(("color", name))) = (
+            definition(path),
+            alias(&token["$value"]).and_then(|target| target.split_once('.')),
+        ) {
+            let derived = ["secondary_bg", "border", "doc_comment"];
+            if !path.starts_with("color.") && !derived.contains(&key.as_str()) {
+                imported.names.insert(key, name.to_string());
+            }
+        }
+
+        match path.split_once('.') {
+            Some(("color", name)) => {
+                imported.colors.insert(name.to_string(), color);
+            }
+            Some(("ui", "accent")) => imported.accent = Some(color),
+            Some(("ui", "background")) => imported.background = Some(color),
+            Some(("ui", "foreground")) => imported.foreground = Some(color),
+            Some(("ui", "secondary_bg")) => imported.derived.secondary_bg = derived(),
+            Some(("ui", "border")) => imported.derived.border = derived(),
+            Some(("syntax", "doc_comment")) => imported.derived.doc_comment = derived(),
+            Some(("syntax", name)) => {
+                if let Some(role) = role(&TOKENS, name) {
+                    imported.tokens.insert(role, color);
+                }
+            }
+            Some(("terminal", name)) => {
+                if let Some(role) = role(&TERMINAL, name) {
+                    imported.terminal.insert(role, color);
+                }
+            }
+            Some(("git", name)) => {
+                if let Some(role) = role(&GIT, name) {
+                    imported.git.insert(role, color);
+                }
+            }
+            Some(("diagnostics", name)) => {
+                if let Some(role) = role(&DIAGNOSTICS, name) {
+                    imported.diagnostics.insert(role, color);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(imported)
+}
+
+/// Writes `dist/dtcg/<theme>.tokens.json`, for Tokens Studio and other
+/// design tools.
+pub struct DtcgExporter;
+
+impl ThemeExporter for DtcgExporter {
+    fn name(&self) -> &str {
+        "dtcg"
+    }
+
+    fn extension(&self) -> &str {
+        "json"
+    }
+
+    fn export(&self, package: &ThemePackage) -> Vec<OutputFile> {
+        package
+            .themes
+            .iter()
+            .filter(|theme| !theme.is_background_syntax)
+            .map(|theme| OutputFile {
+                path: Path::new("dist")
+                    .join("dtcg")
+                    .join(format!("{}.tokens.json", theme.slug())),
+                contents: export(theme, &package.author),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aliases_named_and_derived_colors() {
+        let theme = &crate::snowfall().themes[0];
+        let document: Value = serde_json::from_str(&export(theme, "freethinkel")).unwrap();
+
+        assert_eq!(document["color"]["frost"]["$value"], "#84c4df");
+        assert_eq!(document["ui"]["accent"]["$value"], "{color.frost}");
+        assert_eq!(document["syntax"]["keywords"]["$value"], "{color.heather}");
+        assert_eq!(document["terminal"]["red"]["$value"], "#cc6666");
+        assert_eq!(document["git"]["$type"], "color");
+        assert_eq!(
+            document["ui"]["border"]["$extensions"][EXTENSION]["expression"],
+            "mix(accent, background, 0.9)"
+        );
+
+        let mut aliased = theme.clone();
+        aliased.derived.border = "tokens.comment".into();
+        let document: Value = serde_json::from_str(&export(&aliased, "freethinkel")).unwrap();
+        assert_eq!(document["ui"]["border"]["$value"], "{syntax.comment}");
+    }
+
+    #[test]
+    fn round_trips_through_import() {
+        for theme in crate::snowfall().themes {
+            let payload = import(&export(&theme, "freethinkel"))
+                .unwrap()
+                .complete("fallback".into())
+                .unwrap();
+
+            assert_eq!(payload.name, theme.name);
+            assert_eq!(payload.brightness, theme.brightness);
+            assert_eq!(payload.roles(), theme.payload().roles());
+            assert_eq!(payload.colors, theme.colors);
+            assert_eq!(payload.derived.border, theme.derived.border);
+        }
+    }
+
+    #[test]
+    fn reads_tokens_studio_style_files() {
+        let source = r##"{
+            "color": {
+                "$type": "color",
+                "ice": { "$value": { "colorSpace": "srgb", "components": [1, 1, 1], "alpha": 1 } },
+                "ink": { "$value": "#24272d" }
+            },
+            "ui": {
+                "background": { "$value": "{color.ink}", "$type": "color" },
+                "foreground": { "$value": "{color.ice}", "$type": "color" },
+                "border": { "$value": "{ui.foreground}", "$type": "color" }
+            },
+            "spacing": { "small": { "$value": "4px", "$type": "dimension" } }
+        }"##;
+        let payload = import(source).unwrap().complete("Tokens".into()).unwrap();
+
+        assert_eq!(payload.background, Color::from_hex("#24272d"));
+        assert_eq!(payload.foreground, Color::from_hex("#ffffff"));
+        assert_eq!(payload.derived.border, "foreground");
+        assert_eq!(payload.brightness, Brightness::Dark);
+
+        let cycle = r#"{ "ui": { "$type": "color",
+            "background": { "$value": "{ui.foreground}" },
+            "foreground": { "$value": "{ui.background}" } } }"#;
+        assert!(import(cycle).err().unwrap().contains("Alias cycle"));
+    }
+
+    #[test]
+    fn skips_tokens_outside_the_palette() {
+        let source = r##"{
+            "spacing": { "small": { "$value": "16px" } },
+            "brand": { "primary": { "$value": "#e0a458" } },
+            "ui": {
+                "background": { "$value": "#24272d" },
+                "foreground": { "$value": "#ffffff" },
+                "accent": { "$value": "{brand.primary}" },
+                "radius": { "$value": "4px" }
+            }
+        }"##;
+        let payload = import(source).unwrap().complete("Brand".into()).unwrap();
+
+        assert_eq!(payload.background, Color::from_hex("#24272d"));
+        assert_eq!(payload.accent, Color::from_hex("#e0a458"));
+    }
+
+    #[test]
+    fn resolves_aliases_outside_the_palette() {
+        let source = r##"{
+            "brand": { "$type": "color", "primary": { "$value": "#e0a458" } },
+            "ui": {
+                "$type": "color",
+                "background": { "$value": "#24272d" },
+                "foreground": { "$value": "#ffffff" },
+                "accent": { "$value": "{brand.primary}" },
+                "border": { "$value": "{brand.primary}" }
+            }
+        }"##;
+        let payload = import(source).unwrap().complete("Brand".into()).unwrap();
+
+        assert_eq!(payload.accent, Color::from_hex("#e0a458"));
+        assert_eq!(payload.derived.border, "#e0a458");
+        assert_eq!(
+            crate::Theme::new(payload).unwrap().border(),
+            Color::from_hex("#e0a458")
+        );
+    }
+}
@@ -0,0 +1,113 @@
+use std::path::PathBuf;
+
+use crate::{
+    base16::Base16Exporter,
+    builder::ZedExporter,
+    css::{CssExporter, TailwindExporter},
+    docs::DocsExporter,
+    dtcg::DtcgExporter,
+    helix::HelixExporter,
+    jetbrains::{JetBrainsExporter, JetBrainsUiExporter},
+    kakoune::KakouneExporter,
+    theme::ThemePackage,
+};
+
+/// A generated file, with its path relative to the repository root.
+#[derive(Clone)]
+pub struct OutputFile {
+    pub path: PathBuf,
+    pub contents: String,
+}
+
+/// A target format. Each exporter lives in its own module and is listed in
+/// [`registry`]; `Builder` writes whatever the selected exporters return.
+pub trait ThemeExporter {
+    /// Name used to select the exporter on the command line, e.g. `zed`.
+    fn name(&self) -> &str;
+    /// Extension of the files it writes, without the dot.
+    fn extension(&self) -> &str;
+    fn export(&self, package: &ThemePackage) -> Vec<OutputFile>;
+}
+
+/// Every available exporter, the Zed theme first.
+pub fn registry() -> Vec<Box<dyn ThemeExporter>> {
+    let mut exporters: Vec<Box<dyn ThemeExporter>> = vec![
+        Box::new(ZedExporter),
+        Box::new(Base16Exporter { base24: false }),
+        Box::new(Base16Exporter { base24: true }),
+        Box::new(DtcgExporter),
+        Box::new(CssExporter),
+        Box::new(TailwindExporter),
+        Box::new(HelixExporter),
+        Box::new(KakouneExporter),
+        Box::new(JetBrainsExporter),
+        Box::new(JetBrainsUiExporter),
+    ];
+    for exporter in DocsExporter::all() {
+        exporters.push(Box::new(exporter));
+    }
+    exporters
+}
+
+/// Escapes text for XML and HTML output.
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Exporters by name, or the first unknown name.
+pub fn select(names: &[String]) -> Result<Vec<Box<dyn ThemeExporter>>, String> {
+    names
+        .iter()
+        .map(|name| {
+            registry()
+                .into_iter()
+                .find(|exporter| exporter.name() == name)
+                .ok_or_else(|| format!("Unknown exporter: {}", name))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_are_unique() {
+        let registry = registry();
+        for (index, exporter) in registry.iter().enumerate() {
+            assert!(!registry[index + 1..]
+                .iter()
+                .any(|other| other.name() == exporter.name()));
+        }
+    }
+
+    #[test]
+    fn every_file_has_the_exporter_extension() {
+        let package = crate::snowfall();
+
+        for exporter in registry() {
+            let files = exporter.export(&package);
+            assert!(!files.is_empty(), "{}", exporter.name());
+            for file in files {
+                assert_eq!(
+                    file.path.extension().unwrap(),
+                    exporter.extension(),
+                    "{}",
+                    file.path.display()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn selects_by_name() {
+        let selected = select(&["base24".into(), "zed".into()]).unwrap();
+
+        assert_eq!(selected[0].name(), "base24");
+        assert_eq!(selected[1].name(), "zed");
+        assert!(select(&["vim".into()]).is_err());
+    }
+}
@@ -0,0 +1,496 @@
+use std::{collections::BTreeMap, fmt::Display, ops::Range};
+
+use crate::color::Color;
+
+/// Short names for the colors every palette has.
+const ALIASES: [(&str, &str); 2] = [("bg", "background"), ("fg", "foreground")];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    Color,
+    Number,
+}
+
+impl Display for Kind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Kind::Color => "color",
+            Kind::Number => "number",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Functions expressions can call, with the kinds of their arguments.
+const FUNCTIONS: [(&str, &[Kind]); 7] = [
+    ("mix", &[Kind::Color, Kind::Color, Kind::Number]),
+    ("alpha", &[Kind::Color, Kind::Number]),
+    ("darken", &[Kind::Color, Kind::Number]),
+    ("oklch_lighten", &[Kind::Color, Kind::Number]),
+    ("oklch_darken", &[Kind::Color, Kind::Number]),
+    ("rotate_hue", &[Kind::Color, Kind::Number]),
+    ("scale_chroma", &[Kind::Color, Kind::Number]),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Color(Color),
+    Number(f32),
+    /// A reference to another named color.
+    Name(String),
+    Call(String, Vec<Node>),
+}
+
+/// An expression and the byte range of its source.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node {
+    pub expr: Expr,
+    pub span: Range<usize>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Value {
+    Color(Color),
+    Number(f32),
+}
+
+impl Value {
+    fn kind(&self) -> Kind {
+        match self {
+            Value::Color(_) => Kind::Color,
+            Value::Number(_) => Kind::Number,
+        }
+    }
+}
+
+/// A parse or evaluation error, pointing into the source of the definition
+/// it happened in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Error {
+    /// The definition the error is in, e.g. `tokens.comment`.
+    pub name: String,
+    pub source: String,
+    pub span: Range<usize>,
+    pub message: String,
+}
+
+impl Display for Error {
+    /// The message, then the definition with the span underlined:
+    ///
+    /// ```text
+    /// border: unknown color `acent`
+    ///   border = "mix(acent, background, 0.9)"
+    ///                 ^^^^^
+    /// ```
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let prefix = format!("  {} = \"", self.name);
+        let start = self.source[..self.span.start].chars().count();
+        let width = self.source[self.span.clone()].chars().count().max(1);
+
+        write!(
+            f,
+            "{}: {}\n{}{}\"\n{}{}",
+            self.name,
+            self.message,
+            prefix,
+            self.source,
+            " ".repeat(prefix.chars().count() + start),
+            "^".repeat(width),
+        )
+    }
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    position: usize,
+}
+
+type ParseResult<T> = Result<T, (Range<usize>, String)>;
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<char> {
+        self.source[self.position..].chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        self.take_while(char::is_whitespace);
+    }
+
+    fn take_while(&mut self, f: impl Fn(char) -> bool) -> &'a str {
+        let start = self.position;
+        while self.peek().is_some_and(&f) {
+            self.position += self.peek().unwrap().len_utf8();
+        }
+        &self.source[start..self.position]
+    }
+
+    fn expression(&mut self) -> ParseResult<Node> {
+        self.skip_whitespace();
+        let start = self.position;
+
+        let expr = match self.peek() {
+            None => return Err((start..start, "expected a color, a name or a number".into())),
+            Some('#') => {
+                self.position += 1;
+                let digits = self.take_while(|c| c.is_ascii_alphanumeric());
+                let color = Color::parse(digits).ok_or_else(|| {
+                    (
+                        start..self.position,
+                        format!("`#{}` is not a hex color", digits),
+                    )
+                })?;
+                Expr::Color(color)
+            }
+            Some(c) if c.is_ascii_digit() || c == '-' || c == '.' => {
+                let text = self.take_while(|c| c.is_ascii_digit() || c == '-' || c == '.');
+                let number = text
+                    .parse()
+                    .map_err(|_| (start..self.position, format!("`{}` is not a number", text)))?;
+                Expr::Number(number)
+            }
+            Some(c) if c.is_ascii_alphabetic() || c == '_' => {
+                let name = self
+                    .take_while(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+                    .to_string();
+                let end = self.position;
+                self.skip_whitespace();
+                match self.peek() {
+                    Some('(') => {
+                        self.position += 1;
+                        Expr::Call(name, self.arguments()?)
+                    }
+                    _ => {
+                        self.position = end;
+                        Expr::Name(name)
+                    }
+                }
+            }
+            Some(c) => return Err((start..start + c.len_utf8(), format!("unexpected `{}`", c))),
+        };
+
+        Ok(Node {
+            expr,
+            span: start..self.position,
+        })
+    }
+
+    /// Comma separated arguments up to and including the closing parenthesis.
+    fn arguments(&mut self) -> ParseResult<Vec<Node>> {
+        let mut arguments = vec![];
+        self.skip_whitespace();
+        if self.peek() == Some(')') {
+            self.position += 1;
+            return Ok(arguments);
+        }
+
+        loop {
+            arguments.push(self.expression()?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.position += 1,
+                Some(')') => {
+                    self.position += 1;
+                    return Ok(arguments);
+                }
+                _ => {
+                    return Err((
+                        self.position..self.position + self.peek().map_or(0, char::len_utf8),
+                        "expected `,` or `)`".into(),
+                    ))
+                }
+            }
+        }
+    }
+}
+
+/// Parses a color expression: a hex color (`#84c4df`), a name (`accent`,
+/// `tokens.comment`), a number, or a call such as
+/// `mix(accent, background, 0.9)`.
+pub fn parse(source: &str) -> Result<Node, (Range<usize>, String)> {
+    let mut parser = Parser {
+        source,
+        position: 0,
+    };
+    let node = parser.expression()?;
+    parser.skip_whitespace();
+
+    match parser.peek() {
+        None => Ok(node),
+        Some(_) => Err((
+            parser.position..source.len(),
+            "unexpected input after the expression".into(),
+        )),
+    }
+}
+
+/// Named colors defined by expressions. A name refers to another definition
+/// by its full name (`tokens.comment`), by its last part when that is unique
+/// (`comment`), or by an alias (`bg`, `fg`).
+#[derive(Default)]
+pub struct Palette {
+    definitions: BTreeMap<String, String>,
+}
+
+impl Palette {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn define(&mut self, name: impl Into<String>, source: impl Into<String>) {
+        self.definitions.insert(name.into(), source.into());
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.definitions.keys().map(String::as_str)
+    }
+
+    /// The value of one definition, evaluating only what it depends on.
+    pub fn get(&self, name: &str) -> Result<Color, Error> {
+        self.resolve(name, &mut BTreeMap::new(), &mut vec![])
+    }
+
+    /// Every definition's value, or the first error.
+    pub fn evaluate(&self) -> Result<BTreeMap<String, Color>, Error> {
+        let mut values = BTreeMap::new();
+        for name in self.definitions.keys() {
+            self.resolve(name, &mut values, &mut vec![])?;
+        }
+        Ok(values)
+    }
+
+    /// The definition a reference points to.
+    pub fn lookup(&self, reference: &str) -> Result<&str, String> {
+        if let Some((name, _)) = self.definitions.get_key_value(reference) {
+            return Ok(name);
+        }
+        if let Some((_, name)) = ALIASES.iter().find(|(alias, _)| *alias == reference) {
+            if let Some((name, _)) = self.definitions.get_key_value(*name) {
+                return Ok(name);
+            }
+        }
+
+        let candidates = self
+            .names()
+            .filter(|name| name.rsplit('.').next() == Some(reference))
+            .collect::<Vec<_>>();
+        match candidates[..] {
+            [name] => Ok(name),
+            [] => Err(format!("unknown color `{}`", reference)),
+            _ => Err(format!(
+                "`{}` is ambiguous, it could be {}",
+                reference,
+                candidates.join(" or ")
+            )),
+        }
+    }
+
+    fn resolve(
+        &self,
+        name: &str,
+        values: &mut BTreeMap<String, Color>,
+        stack: &mut Vec<String>,
+    ) -> Result<Color, Error> {
+        if let Some(color) = values.get(name) {
+            return Ok(*color);
+        }
+
+        let Some(source) = self.definitions.get(name) else {
+            return Err(Error {
+                name: name.to_string(),
+                source: String::new(),
+                span: 0..0,
+                message: format!("unknown color `{}`", name),
+            });
+        };
+        let error = |(span, message): (Range<usize>, String)| Error {
+            name: name.to_string(),
+            source: source.clone(),
+            span,
+            message,
+        };
+
+        let node = parse(source).map_err(error)?;
+        stack.push(name.to_string());
+        let value = self.value(&node, values, stack);
+        stack.pop();
+
+        let color = match value? {
+            Value::Color(color) => color,
+            Value::Number(_) => {
+                return Err(error((node.span, "expected a color, got a number".into())))
+            }
+        };
+        values.insert(name.to_string(), color);
+        Ok(color)
+    }
+
+    fn value(
+        &self,
+        node: &Node,
+        values: &mut BTreeMap<String, Color>,
+        stack: &mut Vec<String>,
+    ) -> Result<Value, Error> {
+        let name = stack.last().unwrap().clone();
+        let error = |span: Range<usize>, message: String| Error {
+            name: name.clone(),
+            source: self.definitions[&name].clone(),
+            span,
+            message,
+        };
+
+        match &node.expr {
+            Expr::Color(color) => Ok(Value::Color(*color)),
+            Expr::Number(number) => Ok(Value::Number(*number)),
+            Expr::Name(reference) => {
+                let target = self
+                    .lookup(reference)
+                    .map_err(|message| error(node.span.clone(), message))?;
+                if let Some(index) = stack.iter().position(|name| name == target) {
+                    let cycle = stack[index..]
+                        .iter()
+                        .map(String::as_str)
+                        .chain([target])
+                        .collect::<Vec<_>>();
+                    return Err(error(
+                        node.span.clone(),
+                        format!("cycle: {}", cycle.join(" -> ")),
+                    ));
+                }
+                self.resolve(target, values, stack).map(Value::Color)
+            }
+            Expr::Call(function, arguments) => {
+                let name_span = node.span.start..node.span.start + function.len();
+                let (_, kinds) = FUNCTIONS
+                    .iter()
+                    .find(|(name, _)| name == function)
+                    .ok_or_else(|| error(name_span, format!("unknown function `{}`", function)))?;
+                if arguments.len() != kinds.len() {
+                    let kinds = kinds.iter().map(Kind::to_string).collect::<Vec<_>>();
+                    return Err(error(
+                        node.span.clone(),
+                        format!(
+                            "`{}` takes {} arguments ({}), got {}",
+                            function,
+                            kinds.len(),
+                            kinds.join(", "),
+                            arguments.len()
+                        ),
+                    ));
+                }
+
+                let mut colors = vec![];
+                let mut numbers = vec![];
+                for (argument, kind) in arguments.iter().zip(kinds.iter()) {
+                    match self.value(argument, values, stack)? {
+                        Value::Color(color) if *kind == Kind::Color => colors.push(color),
+                        Value::Number(number) if *kind == Kind::Number => numbers.push(number),
+                        value => {
+                            return Err(error(
+                                argument.span.clone(),
+                                format!("expected a {}, got a {}", kind, value.kind()),
+                            ))
+                        }
+                    }
+                }
+
+                let color = match (function.as_str(), &colors[..], numbers[0]) {
+                    ("mix", [a, b], amount) => a.mix(*b, amount),
+                    ("alpha", [color], opacity) => color.with_opacity(opacity),
+                    ("darken", [color], amount) => color.darken(amount),
+                    ("oklch_lighten", [color], amount) => color.oklch_lighten(amount),
+                    ("oklch_darken", [color], amount) => color.oklch_lighten(-amount),
+                    ("rotate_hue", [color], degrees) => color.rotate_hue(degrees),
+                    ("scale_chroma", [color], factor) => color.scale_chroma(factor),
+                    _ => unreachable!("{} is listed in FUNCTIONS", function),
+                };
+                Ok(Value::Color(color))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn palette(definitions: &[(&str, &str)]) -> Palette {
+        let mut palette = Palette::new();
+        for (name, source) in definitions {
+            palette.define(*name, *source);
+        }
+        palette
+    }
+
+    #[test]
+    fn evaluates_functions_and_references() {
+        let palette = palette(&[
+            ("accent", "#84c4df"),
+            ("background", "#24272d"),
+            ("tokens.comment", "oklch_lighten(bg, 0.2)"),
+            ("border", "mix(accent, background, 0.9)"),
+            ("selection", "alpha(accent, 0.2)"),
+            ("doc", "mix(comment, fg, 0.5)"),
+            ("foreground", "#91a2a6"),
+        ]);
+        let accent = Color::from_hex("#84c4df");
+        let background = Color::from_hex("#24272d");
+
+        assert_eq!(palette.get("border"), Ok(accent.mix(background, 0.9)));
+        assert_eq!(palette.get("selection"), Ok(accent.with_opacity(0.2)));
+        assert_eq!(
+            palette.get("doc"),
+            Ok(background
+                .oklch_lighten(0.2)
+                .mix(Color::from_hex("#91a2a6"), 0.5))
+        );
+        assert_eq!(palette.evaluate().unwrap().len(), 7);
+    }
+
+    #[test]
+    fn detects_cycles() {
+        let palette = palette(&[
+            ("border", "mix(secondary_bg, #000000, 0.5)"),
+            ("secondary_bg", "darken(border, 0.1)"),
+        ]);
+        let error = palette.get("border").unwrap_err();
+
+        assert_eq!(error.name, "secondary_bg");
+        assert_eq!(error.message, "cycle: border -> secondary_bg -> border");
+        assert_eq!(&error.source[error.span], "border");
+    }
+
+    #[test]
+    fn reports_unknown_names() {
+        let error = palette(&[("accent", "#84c4df")]).get("border").unwrap_err();
+
+        assert_eq!(error.name, "border");
+        assert_eq!(error.message, "unknown color `border`");
+    }
+
+    #[test]
+    fn points_at_the_problem() {
+        let error = |source: &str| {
+            palette(&[("accent", "#84c4df"), ("border", source)])
+                .get("border")
+                .unwrap_err()
+        };
+
+        assert_eq!(
+            error("mix(acent, accent, 0.9)").to_string(),
+            "border: unknown color `acent`\n  border = \"mix(acent, accent, 0.9)\"\n                ^^^^^"
+        );
+        let wrong_kind = error("alpha(accent, accent)");
+        assert_eq!(wrong_kind.message, "expected a number, got a color");
+        assert_eq!(wrong_kind.span, 14..20);
+        assert!(error("mix(accent, 0.9)")
+            .message
+            .contains("takes 3 arguments"));
+        assert_eq!(error("lighten(accent, 0.1)").span, 0..7);
+        assert_eq!(
+            error("mix(accent, accent 0.9)").message,
+            "expected `,` or `)`"
+        );
+        assert_eq!(error("#84c4dg").message, "`#84c4dg` is not a hex color");
+        assert_eq!(error("0.5").message, "expected a color, got a number");
+    }
+}
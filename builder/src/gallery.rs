@@ -0,0 +1,163 @@
+use std::{fmt::Write, fs, io, path::Path};
+
+use crate::{
+    diff::{self, Subsystem},
+    export::escape,
+    highlight::{self, SampleHighlighter},
+    theme::ThemePackage,
+};
+
+/// WCAG 2 level a contrast ratio reaches, for the badge next to each swatch.
+fn level(contrast: f32) -> &'static str {
+    match contrast {
+        contrast if contrast >= 7.0 => "AAA",
+        contrast if contrast >= 4.5 => "AA",
+        contrast if contrast >= 3.0 => "AA18",
+        _ => "fail",
+    }
+}
+
+/// One self-contained HTML page comparing every theme of a package side by
+/// side: syntax samples, then every style key with its swatch (over a
+/// checkerboard, so alpha shows) and a contrast badge. No external assets.
+pub fn html(package: &ThemePackage, highlighter: &SampleHighlighter) -> String {
+    let styles = package
+        .themes
+        .iter()
+        .map(|theme| {
+            let value = serde_json::to_value(theme).unwrap();
+            diff::colors(&value["style"])
+        })
+        .collect::<Vec<_>>();
+
+    let mut keys: Vec<&str> = vec![];
+    for (key, _) in styles.iter().flatten() {
+        if !keys.contains(&key.as_str()) {
+            keys.push(key);
+        }
+    }
+    keys.sort_by_key(|key| Subsystem::of(key));
+
+    let mut body = String::from("<table><thead><tr><th></th>");
+    for theme in &package.themes {
+        let _ = write!(body, "<th>{}</th>", escape(&theme.name));
+    }
+    body.push_str("</tr></thead><tbody>");
+
+    for (language, source) in &highlight::SAMPLES[..2] {
+        let _ = write!(body, r#"<tr><th class="key">{}</th>"#, language);
+        for theme in &package.themes {
+            let _ = write!(
+                body,
+                "<td>{}</td>",
+                highlight::pre(theme, highlighter, language, source)
+            );
+        }
+        body.push_str("</tr>");
+    }
+
+    let mut subsystem = None;
+    for key in keys {
+        if subsystem != Some(Subsystem::of(key)) {
+            subsystem = Some(Subsystem::of(key));
+            let _ = write!(
+                body,
+                r#"<tr><th class="group" colspan="{}">{}</th></tr>"#,
+                package.themes.len() + 1,
+                Subsystem::of(key)
+            );
+        }
+
+        let _ = write!(body, r#"<tr><th class="key">{}</th>"#, key);
+        for (theme, style) in package.themes.iter().zip(&styles) {
+            let Some((_, color)) = style.iter().find(|(name, _)| name == key) else {
+                body.push_str("<td></td>");
+                continue;
+            };
+            let _ = write!(
+                body,
+                r#"<td style="background: {}; color: {}"><span class="swatch"><i style="background: {}"></i></span><code>{}</code>"#,
+                theme.background.to_css(),
+                theme.foreground.to_css(),
+                color.to_css(),
+                color.to_hex(),
+            );
+            if let Some(contrast) = diff::contrast(style, key, *color) {
+                let _ = write!(
+                    body,
+                    r#" <span class="badge {}">{:.1} {}</span>"#,
+                    level(contrast).to_lowercase(),
+                    contrast,
+                    level(contrast),
+                );
+            }
+            body.push_str("</td>");
+        }
+        body.push_str("</tr>");
+    }
+    body.push_str("</tbody></table>");
+
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>{name}</title>
+<style>
+body {{ margin: 0; background: #f4f4f5; color: #27272a; font: 13px/1.4 system-ui, sans-serif; }}
+h1 {{ font-size: 20px; padding: 16px 24px 0; }}
+table {{ border-collapse: collapse; margin: 0 24px 24px; }}
+th, td {{ padding: 4px 8px; text-align: left; vertical-align: middle; white-space: nowrap; }}
+thead th {{ position: sticky; top: 0; background: #f4f4f5; font-size: 14px; padding: 8px; }}
+th.key {{ font: 12px monospace; font-weight: normal; }}
+th.group {{ padding-top: 20px; font-size: 14px; text-transform: capitalize; }}
+pre {{ margin: 0; padding: 12px; font: 12px/18px monospace; border-radius: 6px; }}
+code {{ font: 12px monospace; }}
+.swatch {{ display: inline-block; width: 28px; height: 16px; margin-right: 8px; vertical-align: middle; border-radius: 3px; overflow: hidden;
+  background: repeating-conic-gradient(#c8c8c8 0 25%, #ffffff 0 50%) 0 0 / 8px 8px; }}
+.swatch i {{ display: block; width: 100%; height: 100%; }}
+.badge {{ font-size: 10px; padding: 1px 4px; border-radius: 3px; color: #ffffff; }}
+.badge.aaa {{ background: #15803d; }}
+.badge.aa {{ background: #4d7c0f; }}
+.badge.aa18 {{ background: #b45309; }}
+.badge.fail {{ background: #b91c1c; }}
+</style>
+</head>
+<body>
+<h1>{name}</h1>
+{body}
+</body>
+</html>
+"#,
+        name = escape(&package.name),
+        body = body,
+    )
+}
+
+/// Writes `dist/gallery.html` under `root`.
+pub fn write(root: &Path, package: &ThemePackage) -> io::Result<()> {
+    let dir = root.join("dist");
+    fs::create_dir_all(&dir)?;
+    fs::write(
+        dir.join("gallery.html"),
+        html(package, &SampleHighlighter::new()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lists_every_style_key_for_every_theme() {
+        let package = crate::snowfall();
+        let html = html(&package, &SampleHighlighter::new());
+
+        for theme in &package.themes {
+            assert!(html.contains(&format!("<th>{}</th>", escape(&theme.name))));
+        }
+        assert!(html.contains(r#"<th class="key">editor.background</th>"#));
+        assert!(html.contains(r#"<th class="key">terminal.ansi.bright_white</th>"#));
+        assert!(!html.contains("http://") && !html.contains("https://") && !html.contains("src="));
+    }
+}
@@ -0,0 +1,297 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    color::Color,
+    theme::{
+        Brightness, ThemeCreatePayload, ThemeDerived, ThemeDiagnostic, ThemeGit, ThemeTerminal,
+        ThemeTokens,
+    },
+};
+
+/// Minimum WCAG contrast against the background for syntax tokens.
+pub const TOKEN_CONTRAST: f32 = 4.5;
+/// Minimum contrast for terminal colors, which are mostly used for short,
+/// bold bits of output.
+pub const TERMINAL_CONTRAST: f32 = 3.0;
+/// Comments are meant to recede, but must stay readable.
+pub const COMMENT_CONTRAST: f32 = 2.2;
+
+/// Default OKLCH hues for red, yellow, green, cyan, blue and magenta, in the
+/// order of [`Seeds::hues`].
+pub const DEFAULT_HUES: [f32; 6] = [22.0, 85.0, 125.0, 210.0, 250.0, 320.0];
+
+/// The few colors a palette is generated from.
+pub struct Seeds {
+    pub name: String,
+    pub background: Color,
+    pub foreground: Color,
+    pub accent: Color,
+    /// OKLCH hues for red, yellow, green, cyan, blue and magenta.
+    pub hues: [f32; 6],
+}
+
+impl Seeds {
+    pub fn new(name: String, background: Color, foreground: Color, accent: Color) -> Self {
+        Self {
+            name,
+            background,
+            foreground,
+            accent,
+            hues: DEFAULT_HUES,
+        }
+    }
+}
+
+/// A generated palette, with every color that could not reach its minimum
+/// contrast.
+pub struct Generated {
+    pub payload: ThemeCreatePayload,
+    pub warnings: Vec<String>,
+}
+
+/// A color of the given OKLCH lightness, chroma and hue, moved away from the
+/// background lightness in small steps until it reaches `contrast`, or the
+/// darkest or lightest color of that hue when it can't.
+fn readable(background: Color, lightness: f32, chroma: f32, hue: f32, contrast: f32) -> Color {
+    let away = match background.to_oklch().0 < 0.5 {
+        true => 0.01,
+        false => -0.01,
+    };
+    let mut lightness = lightness;
+
+    loop {
+        let color = Color::from_oklch(lightness, chroma, hue, 255);
+        if color.contrast(background) >= contrast || !(0.0..=1.0).contains(&lightness) {
+            return color;
+        }
+        lightness += away;
+    }
+}
+
+/// A complete palette from a background, foreground and accent. Every hue
+/// shares one lightness and one chroma (taken from the accent, within
+/// reason), so nothing jumps out, and every color is nudged until it reaches
+/// [`TOKEN_CONTRAST`] or [`TERMINAL_CONTRAST`] against the background.
+/// Colors that can't get there, e.g. on a mid-gray background, are kept and
+/// reported in [`Generated::warnings`].
+pub fn generate(seeds: &Seeds) -> Generated {
+    let background = seeds.background;
+    let foreground = seeds.foreground;
+    let brightness = match background.to_oklch().0 < 0.5 {
+        true => Brightness::Dark,
+        false => Brightness::Light,
+    };
+    // Bright terminal colors step away from the background, as in Snowfall.
+    let step = match brightness {
+        Brightness::Dark => 0.08,
+        Brightness::Light => -0.08,
+    };
+
+    let (accent_lightness, accent_chroma, accent_hue) = seeds.accent.to_oklch();
+    let lightness = (foreground.to_oklch().0 + accent_lightness) / 2.0;
+    let chroma = accent_chroma.clamp(0.06, 0.14);
+    let [red, yellow, green, cyan, blue, magenta] = seeds.hues;
+
+    let token =
+        |hue: f32, chroma: f32| readable(background, lightness, chroma, hue, TOKEN_CONTRAST);
+    let functions = token(accent_hue, chroma);
+    let keywords = token(magenta, chroma);
+    let constants = token(yellow, chroma);
+    let tokens = ThemeTokens {
+        brackets: token(accent_hue, chroma * 0.6),
+        comment: readable(
+            background,
+            background.to_oklch().0,
+            chroma * 0.15,
+            accent_hue,
+            COMMENT_CONTRAST,
+        ),
+        strings: token(green, chroma),
+        types: token(cyan, chroma * 0.4),
+        functions,
+        properties: constants,
+        keywords,
+        constants,
+        operators: token(magenta, chroma * 0.5),
+    };
+
+    let ansi =
+        |hue: f32, lightness: f32| readable(background, lightness, chroma, hue, TERMINAL_CONTRAST);
+    let normal = lightness;
+    let bright = lightness + step;
+    let (black, white) = match brightness {
+        Brightness::Dark => (
+            background.darken(0.25),
+            foreground.mix(Color::from_hex("#ffffff"), 0.3),
+        ),
+        Brightness::Light => (foreground.darken(0.5), background.darken(0.15)),
+    };
+    let terminal = ThemeTerminal {
+        black,
+        red: ansi(red, normal),
+        green: ansi(green, normal),
+        yellow: ansi(yellow, normal),
+        blue: ansi(blue, normal),
+        magenta: ansi(magenta, normal),
+        cyan: ansi(cyan, normal),
+        white,
+        bright_black: black.mix(white, 0.35),
+        bright_red: ansi(red, bright),
+        bright_green: ansi(green, bright),
+        bright_yellow: ansi(yellow, bright),
+        bright_blue: ansi(blue, bright),
+        bright_magenta: ansi(magenta, bright),
+        bright_cyan: ansi(cyan, bright),
+        bright_white: match brightness {
+            Brightness::Dark => Color::from_hex("#ffffff"),
+            Brightness::Light => background.darken(0.3),
+        },
+    };
+
+    let git = ThemeGit {
+        added: token(green, chroma),
+        modified: token(accent_hue, chroma),
+        removed: token(red, chroma),
+    };
+    let diagnostics = ThemeDiagnostic {
+        error: token(red, chroma * 1.2),
+        warning: token(yellow, chroma * 1.2),
+        info: token(blue, chroma),
+    };
+
+    let payload = ThemeCreatePayload {
+        name: seeds.name.clone(),
+        brightness,
+        accent: seeds.accent,
+        background,
+        foreground,
+        tokens,
+        terminal,
+        diagnostics,
+        git,
+        colors: BTreeMap::new(),
+        names: BTreeMap::new(),
+        derived: ThemeDerived::default(),
+    };
+    Generated {
+        warnings: warnings(&payload),
+        payload,
+    }
+}
+
+/// Every generated color below its minimum contrast against the background.
+fn warnings(payload: &ThemeCreatePayload) -> Vec<String> {
+    let mut checks = vec![];
+    for (role, color) in payload.tokens.colors() {
+        let minimum = match role {
+            "comment" => COMMENT_CONTRAST,
+            _ => TOKEN_CONTRAST,
+        };
+        checks.push((format!("tokens.{}", role), color, minimum));
+    }
+    for (role, color) in payload.terminal.colors() {
+        if !role.contains("black") && !role.contains("white") {
+            checks.push((format!("terminal.{}", role), color, TERMINAL_CONTRAST));
+        }
+    }
+    for (role, color) in payload.git.colors() {
+        checks.push((format!("git.{}", role), color, TOKEN_CONTRAST));
+    }
+    for (role, color) in payload.diagnostics.colors() {
+        checks.push((format!("diagnostics.{}", role), color, TOKEN_CONTRAST));
+    }
+
+    checks
+        .into_iter()
+        .filter_map(|(role, color, minimum)| {
+            let contrast = color.contrast(payload.background);
+            (contrast < minimum).then(|| {
+                format!(
+                    "{} ({}) only reaches {:.2}:1 contrast against the background, below {}:1",
+                    role,
+                    color.to_hex(),
+                    contrast,
+                    minimum
+                )
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seeds() -> [Seeds; 2] {
+        [
+            Seeds::new(
+                "Generated dark".into(),
+                Color::from_hex("#24272d"),
+                Color::from_hex("#91a2a6"),
+                Color::from_hex("#84c4df"),
+            ),
+            Seeds::new(
+                "Generated light".into(),
+                Color::from_hex("#ffffff"),
+                Color::from_hex("#5c6165"),
+                Color::from_hex("#88c0d0"),
+            ),
+        ]
+    }
+
+    #[test]
+    fn every_color_is_readable() {
+        for seeds in seeds() {
+            let Generated { payload, warnings } = generate(&seeds);
+
+            assert!(warnings.is_empty(), "{:?}", warnings);
+            for (role, color) in payload.tokens.colors() {
+                let minimum = match role {
+                    "comment" => COMMENT_CONTRAST,
+                    _ => TOKEN_CONTRAST,
+                };
+                assert!(color.contrast(seeds.background) >= minimum, "{}", role);
+            }
+            for (_, color) in payload.diagnostics.colors() {
+                assert!(color.contrast(seeds.background) >= TOKEN_CONTRAST);
+            }
+            for (role, color) in payload.terminal.colors() {
+                if !role.contains("black") && !role.contains("white") {
+                    assert!(color.contrast(seeds.background) >= TERMINAL_CONTRAST);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn uses_the_given_hues() {
+        let [mut seeds, _] = seeds();
+        seeds.hues[5] = 140.0;
+        let payload = generate(&seeds).payload;
+        let (_, _, hue) = payload.tokens.keywords.to_oklch();
+
+        assert_eq!(payload.brightness, Brightness::Dark);
+        assert!((hue - 140.0).abs() < 5.0);
+    }
+
+    #[test]
+    fn warns_about_unreachable_contrast() {
+        // Nothing reaches 4.5:1 against a mid gray in the one direction
+        // colors are moved.
+        let seeds = Seeds::new(
+            "Gray".into(),
+            Color::from_hex("#6e6e6e"),
+            Color::from_hex("#000000"),
+            Color::from_hex("#84c4df"),
+        );
+        let Generated { payload, warnings } = generate(&seeds);
+
+        assert!(warnings
+            .iter()
+            .any(|warning| warning.starts_with("tokens.keywords (")));
+        assert!(payload.tokens.keywords.contrast(seeds.background) < TOKEN_CONTRAST);
+        assert!(warnings
+            .iter()
+            .all(|warning| warning.contains(":1 contrast")));
+    }
+}
@@ -0,0 +1,185 @@
+use std::fmt::{Display, Write};
+
+use crate::{color::Color, colorblind::COLLAPSE_THRESHOLD, theme::Theme};
+
+/// Under this CIEDE2000 distance two colors are the same color picked twice,
+/// most likely by accident.
+pub const NEAR_DUPLICATE_THRESHOLD: f32 = 5.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Closeness {
+    /// Exactly the same color, usually a deliberate shared role.
+    Shared,
+    /// Almost the same color, but not quite.
+    NearDuplicate,
+    /// Different colors that still read as the same at a glance.
+    Close,
+}
+
+impl Display for Closeness {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Closeness::Shared => "shared",
+            Closeness::NearDuplicate => "near duplicate",
+            Closeness::Close => "close",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+pub struct Pair {
+    pub first: (String, Color),
+    pub second: (String, Color),
+    pub delta_e: f32,
+    /// Euclidean distance in OKLab, where 0.02 is about a just noticeable
+    /// difference.
+    pub oklab: f32,
+    pub closeness: Closeness,
+}
+
+fn oklab_distance(a: Color, b: Color) -> f32 {
+    let (a, b) = (a.to_oklab(), b.to_oklab());
+    a.iter()
+        .zip(b)
+        .map(|(a, b)| (a - b).powi(2))
+        .sum::<f32>()
+        .sqrt()
+}
+
+/// Pairs of token roles, and of token roles with terminal colors, that are
+/// closer than [`COLLAPSE_THRESHOLD`], closest first.
+pub fn pairs(theme: &Theme) -> Vec<Pair> {
+    let named = |prefix: &str, colors: Vec<(&str, Color)>| {
+        colors
+            .into_iter()
+            .map(|(name, color)| (format!("{}.{}", prefix, name), color))
+            .collect::<Vec<_>>()
+    };
+    let tokens = named("tokens", theme.tokens.colors());
+    let terminal = named("terminal", theme.terminal.colors());
+
+    let candidates = tokens
+        .iter()
+        .enumerate()
+        .flat_map(|(index, first)| {
+            tokens[index + 1..]
+                .iter()
+                .chain(&terminal)
+                .map(move |second| (first, second))
+        })
+        .collect::<Vec<_>>();
+
+    let mut pairs = vec![];
+    for (first, second) in candidates {
+        let delta_e = first.1.delta_e(second.1);
+        let closeness = match delta_e {
+            _ if first.1.with_opacity(1.0) == second.1.with_opacity(1.0) => Closeness::Shared,
+            delta_e if delta_e < NEAR_DUPLICATE_THRESHOLD => Closeness::NearDuplicate,
+            delta_e if delta_e < COLLAPSE_THRESHOLD => Closeness::Close,
+            _ => continue,
+        };
+        pairs.push(Pair {
+            first: first.clone(),
+            second: second.clone(),
+            delta_e,
+            oklab: oklab_distance(first.1, second.1),
+            closeness,
+        });
+    }
+
+    pairs.sort_by(|a, b| a.delta_e.total_cmp(&b.delta_e));
+    pairs
+}
+
+/// Token roles that can't be told apart at a glance, grouped transitively:
+/// if `types` is close to `brackets` and `brackets` to `functions`, all three
+/// end up in one group.
+pub fn indistinguishable(theme: &Theme) -> Vec<Vec<&'static str>> {
+    let tokens = theme.tokens.colors();
+    let mut groups: Vec<Vec<&'static str>> = vec![];
+
+    for (name, color) in &tokens {
+        let mut group = vec![*name];
+        let mut rest = vec![];
+        for existing in groups {
+            let close = existing.iter().any(|role| {
+                tokens
+                    .iter()
+                    .any(|(other, c)| other == role && c.delta_e(*color) < COLLAPSE_THRESHOLD)
+            });
+            match close {
+                true => group.extend(existing),
+                false => rest.push(existing),
+            }
+        }
+        rest.push(group);
+        groups = rest;
+    }
+
+    groups.retain(|group| group.len() > 1);
+    groups
+}
+
+pub fn report(theme: &Theme) -> String {
+    let pairs = pairs(theme);
+    let mut report = format!("{}\n", theme.name);
+
+    if pairs.is_empty() {
+        report.push_str("  every role is distinct\n");
+    }
+    for pair in &pairs {
+        let _ = writeln!(
+            report,
+            "  {:<14} {} {} vs {} {}: ΔE {:.1}, OKLab {:.3}",
+            pair.closeness.to_string(),
+            pair.first.0,
+            pair.first.1.to_hex(),
+            pair.second.0,
+            pair.second.1.to_hex(),
+            pair.delta_e,
+            pair.oklab,
+        );
+    }
+    for group in indistinguishable(theme) {
+        let _ = writeln!(
+            report,
+            "  indistinguishable at a glance: {}",
+            group.join(", ")
+        );
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_shared_and_near_duplicate_roles() {
+        let theme = &crate::snowfall().themes[0];
+        let pairs = pairs(theme);
+        let closeness = |first: &str, second: &str| {
+            pairs
+                .iter()
+                .find(|pair| pair.first.0 == first && pair.second.0 == second)
+                .map(|pair| pair.closeness)
+        };
+
+        assert_eq!(
+            closeness("tokens.keywords", "tokens.operators"),
+            Some(Closeness::Shared)
+        );
+        assert_eq!(
+            closeness("tokens.brackets", "tokens.functions"),
+            Some(Closeness::Shared)
+        );
+        assert_eq!(
+            closeness("tokens.brackets", "terminal.cyan"),
+            Some(Closeness::NearDuplicate)
+        );
+        assert!(indistinguishable(theme)
+            .iter()
+            .any(|group| group.contains(&"brackets") && group.contains(&"functions")));
+    }
+}
@@ -0,0 +1,302 @@
+use std::{fmt::Write, path::Path};
+
+use crate::{
+    builder::SyntaxStyle,
+    color::Color,
+    docs::style,
+    export::{OutputFile, ThemeExporter},
+    theme::{Theme, ThemePackage},
+};
+
+/// Helix scopes and the Zed capture whose style they take. Both name their
+/// scopes after tree-sitter captures, so most map one to one.
+const SCOPES: [(&str, &str); 42] = [
+    ("attribute", "attribute"),
+    ("type", "type"),
+    ("type.builtin", "type"),
+    ("constructor", "constructor"),
+    ("constant", "constant"),
+    ("constant.builtin", "boolean"),
+    ("constant.character", "character"),
+    ("constant.character.escape", "string.escape"),
+    ("constant.numeric", "number"),
+    ("string", "string"),
+    ("string.regexp", "string.regex"),
+    ("string.special", "string.special"),
+    ("string.special.symbol", "string.special.symbol"),
+    ("comment", "comment"),
+    ("comment.block.documentation", "comment.doc"),
+    ("variable", "variable"),
+    ("variable.builtin", "variable.special"),
+    ("variable.parameter", "variable"),
+    ("variable.other.member", "property"),
+    ("label", "label"),
+    ("punctuation", "punctuation"),
+    ("punctuation.bracket", "punctuation.bracket"),
+    ("punctuation.delimiter", "punctuation.delimiter"),
+    ("punctuation.special", "punctuation.special"),
+    ("keyword", "keyword"),
+    ("operator", "operator"),
+    ("function", "function"),
+    ("function.macro", "function"),
+    ("tag", "tag"),
+    ("namespace", "type"),
+    ("special", "punctuation.special"),
+    ("markup.heading", "title"),
+    ("markup.list", "punctuation.list_marker"),
+    ("markup.bold", "emphasis.strong"),
+    ("markup.italic", "emphasis"),
+    ("markup.link.url", "link_uri"),
+    ("markup.link.text", "link_text"),
+    ("markup.quote", "comment"),
+    ("markup.raw", "text.literal"),
+    ("diff.plus", "diff.plus"),
+    ("diff.minus", "diff.minus"),
+    ("diff.delta", "diff.delta"),
+];
+
+/// A color as Helix sees it: composited over the background, since Helix
+/// has no alpha, and written as the named color it equals when there is one.
+fn value(theme: &Theme, color: Color) -> String {
+    let color = color.over(theme.background);
+    theme
+        .colors
+        .iter()
+        .find(|(_, named)| named.over(theme.background) == color)
+        .map(|(name, _)| name.clone())
+        .unwrap_or_else(|| color.to_hex())
+}
+
+fn entry(theme: &Theme, key: &str, style: &SyntaxStyle) -> String {
+    let mut fields = vec![format!("fg = \"{}\"", value(theme, style.color))];
+    if let Some(background) = style.background {
+        fields.push(format!("bg = \"{}\"", value(theme, background)));
+    }
+    let modifiers = [("bold", style.bold), ("italic", style.italic)]
+        .into_iter()
+        .filter(|(_, on)| *on)
+        .map(|(modifier, _)| format!("\"{}\"", modifier))
+        .collect::<Vec<_>>();
+    if !modifiers.is_empty() {
+        fields.push(format!("modifiers = [{}]", modifiers.join(", ")));
+    }
+    match fields.len() {
+        1 => format!("\"{}\" = \"{}\"\n", key, value(theme, style.color)),
+        _ => format!("\"{}\" = {{ {} }}\n", key, fields.join(", ")),
+    }
+}
+
+/// A Helix theme: syntax scopes, the editor UI from the surfaces, borders,
+/// selection and diagnostics, and the named colors as its palette.
+pub fn theme(theme: &Theme) -> String {
+    let mut toml = format!("# {}, generated by snowfall-zed\n\n", theme.name);
+    for (scope, capture) in SCOPES {
+        toml.push_str(&entry(theme, scope, &style(theme, capture)));
+    }
+
+    let color = |color: Color| value(theme, color);
+    let background = color(theme.background);
+    let foreground = color(theme.foreground);
+    let secondary_bg = color(theme.secondary_bg());
+    let border = color(theme.border());
+    let accent = color(theme.accent);
+    let selection = color(theme.accent.with_opacity(0.2));
+    let muted = color(theme.foreground.with_opacity(0.5));
+    let line_number = color(theme.foreground.with_opacity(0.4));
+
+    toml.push('\n');
+    let ui = [
+        ("ui.background", format!("{{ bg = \"{}\" }}", background)),
+        ("ui.text", format!("\"{}\"", foreground)),
+        (
+            "ui.text.focus",
+            format!("{{ fg = \"{}\", modifiers = [\"bold\"] }}", foreground),
+        ),
+        ("ui.text.inactive", format!("\"{}\"", muted)),
+        (
+            "ui.cursor",
+            format!("{{ fg = \"{}\", bg = \"{}\" }}", background, muted),
+        ),
+        (
+            "ui.cursor.primary",
+            format!("{{ fg = \"{}\", bg = \"{}\" }}", background, accent),
+        ),
+        (
+            "ui.cursor.match",
+            format!("{{ bg = \"{}\", modifiers = [\"bold\"] }}", selection),
+        ),
+        ("ui.selection", format!("{{ bg = \"{}\" }}", selection)),
+        (
+            "ui.cursorline.primary",
+            format!("{{ bg = \"{}\" }}", secondary_bg),
+        ),
+        ("ui.linenr", format!("\"{}\"", line_number)),
+        ("ui.linenr.selected", format!("\"{}\"", foreground)),
+        (
+            "ui.statusline",
+            format!("{{ fg = \"{}\", bg = \"{}\" }}", foreground, secondary_bg),
+        ),
+        (
+            "ui.statusline.inactive",
+            format!("{{ fg = \"{}\", bg = \"{}\" }}", muted, secondary_bg),
+        ),
+        (
+            "ui.statusline.normal",
+            format!(
+                "{{ fg = \"{}\", bg = \"{}\", modifiers = [\"bold\"] }}",
+                background, accent
+            ),
+        ),
+        (
+            "ui.popup",
+            format!("{{ fg = \"{}\", bg = \"{}\" }}", foreground, secondary_bg),
+        ),
+        ("ui.window", format!("\"{}\"", border)),
+        (
+            "ui.help",
+            format!("{{ fg = \"{}\", bg = \"{}\" }}", foreground, secondary_bg),
+        ),
+        (
+            "ui.menu",
+            format!("{{ fg = \"{}\", bg = \"{}\" }}", foreground, secondary_bg),
+        ),
+        ("ui.menu.selected", format!("{{ bg = \"{}\" }}", selection)),
+        ("ui.virtual.whitespace", format!("\"{}\"", border)),
+        ("ui.virtual.indent-guide", format!("\"{}\"", border)),
+        (
+            "ui.virtual.ruler",
+            format!("{{ bg = \"{}\" }}", secondary_bg),
+        ),
+        ("ui.virtual.inlay-hint", format!("\"{}\"", muted)),
+    ];
+    for (key, style) in ui {
+        let _ = writeln!(toml, "\"{}\" = {}", key, style);
+    }
+
+    toml.push('\n');
+    let diagnostics = [
+        ("error", theme.diagnostics.error),
+        ("warning", theme.diagnostics.warning),
+        ("info", theme.diagnostics.info),
+        ("hint", theme.foreground.with_opacity(0.5)),
+    ];
+    for (level, level_color) in diagnostics {
+        let level_color = color(level_color);
+        let _ = writeln!(toml, "\"{}\" = \"{}\"", level, level_color);
+        let _ = writeln!(
+            toml,
+            "\"diagnostic.{}\" = {{ underline = {{ color = \"{}\", style = \"curl\" }} }}",
+            level, level_color
+        );
+    }
+
+    if !theme.colors.is_empty() {
+        toml.push_str("\n[palette]\n");
+        // Names are arbitrary, so keys are quoted: `blue.light` would
+        // otherwise be a nested table.
+        for (name, named) in &theme.colors {
+            let _ = writeln!(
+                toml,
+                "{} = \"{}\"",
+                toml::Value::String(name.clone()),
+                named.over(theme.background).to_hex()
+            );
+        }
+    }
+    toml
+}
+
+/// Writes one Helix theme per theme to `dist/helix/themes/<theme>.toml`,
+/// ready to copy into `~/.config/helix/themes`.
+pub struct HelixExporter;
+
+impl ThemeExporter for HelixExporter {
+    fn name(&self) -> &str {
+        "helix"
+    }
+
+    fn extension(&self) -> &str {
+        "toml"
+    }
+
+    fn export(&self, package: &ThemePackage) -> Vec<OutputFile> {
+        package
+            .themes
+            .iter()
+            .map(|theme| OutputFile {
+                path: Path::new("dist")
+                    .join("helix")
+                    .join("themes")
+                    .join(format!("{}.toml", theme.slug())),
+                contents: self::theme(theme),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_and_resolves_through_the_palette() {
+        for theme in crate::snowfall().themes {
+            let toml = self::theme(&theme);
+            let table = toml::from_str::<toml::Table>(&toml).unwrap();
+            let palette = table["palette"].as_table().unwrap();
+            let resolve = |value: &toml::Value| {
+                let value = value.as_str().unwrap();
+                palette
+                    .get(value)
+                    .and_then(toml::Value::as_str)
+                    .unwrap_or(value)
+                    .to_string()
+            };
+
+            assert_eq!(resolve(&table["keyword"]), theme.tokens.keywords.to_hex());
+            assert_eq!(
+                resolve(&table["ui.background"]["bg"]),
+                theme.background.to_hex()
+            );
+            assert_eq!(
+                resolve(&table["error"]),
+                theme.diagnostics.error.over(theme.background).to_hex()
+            );
+            assert_eq!(
+                resolve(&table["diff.plus"]),
+                theme.git.added.over(theme.background).to_hex()
+            );
+            assert!(table.contains_key("ui.selection"));
+            assert!(table.contains_key("diagnostic.warning"));
+        }
+    }
+
+    #[test]
+    fn writes_named_colors_by_name() {
+        let theme = &crate::snowfall().themes[0];
+        let toml = self::theme(theme);
+
+        assert!(toml.contains("\"keyword\" = \"heather\"\n"));
+        assert!(toml.contains("\n[palette]\n\"berry\" = \""));
+        assert!(toml.contains("\"type\" = { fg = \""));
+    }
+
+    #[test]
+    fn quotes_palette_names() {
+        let mut theme = crate::snowfall().themes[0].clone();
+        theme
+            .colors
+            .insert("blue.light".into(), theme.tokens.keywords);
+        theme.colors.insert("deep sea".into(), theme.accent);
+        let toml = self::theme(&theme);
+        let table = toml::from_str::<toml::Table>(&toml).unwrap();
+        let palette = table["palette"].as_table().unwrap();
+
+        assert_eq!(table["keyword"].as_str(), Some("blue.light"));
+        assert_eq!(
+            palette["blue.light"].as_str(),
+            Some(theme.tokens.keywords.to_hex().as_str())
+        );
+        assert!(palette.contains_key("deep sea"));
+    }
+}
@@ -0,0 +1,298 @@
+use std::{fmt::Write, fs, io, path::Path};
+
+use tree_sitter_highlight::{HighlightConfiguration, HighlightEvent, Highlighter};
+
+use crate::{builder::SyntaxTheme, export::escape, theme::Theme};
+
+/// Sample files shipped with the builder, highlighted with the grammars' own
+/// queries.
+pub const SAMPLES: [(&str, &str); 6] = [
+    ("rust", include_str!("../samples/snowflake.rs")),
+    ("typescript", include_str!("../samples/snowflake.ts")),
+    ("python", include_str!("../samples/snowflake.py")),
+    ("go", include_str!("../samples/snowflake.go")),
+    ("markdown", include_str!("../samples/snowflake.md")),
+    ("json", include_str!("../samples/snowflake.json")),
+];
+
+/// A run of text and the innermost capture it belongs to, e.g.
+/// `punctuation.bracket`. `None` is plain foreground text.
+pub type Span = (Option<String>, String);
+
+/// Tree-sitter highlighter for the sample languages. Capture names are kept
+/// as the queries spell them and resolved against the theme later, so Zed's
+/// dotted fallback applies.
+pub struct SampleHighlighter {
+    configurations: Vec<HighlightConfiguration>,
+    names: Vec<String>,
+}
+
+impl SampleHighlighter {
+    pub fn new() -> Self {
+        let typescript = [
+            tree_sitter_typescript::HIGHLIGHTS_QUERY,
+            tree_sitter_javascript::HIGHLIGHT_QUERY,
+        ]
+        .join("\n");
+        let configuration =
+            |language: tree_sitter::Language, name: &str, highlights: &str, injections: &str| {
+                HighlightConfiguration::new(language, name, highlights, injections, "")
+                    .expect("Error loading highlight query")
+            };
+
+        let mut configurations = vec![
+            configuration(
+                tree_sitter_rust::LANGUAGE.into(),
+                "rust",
+                tree_sitter_rust::HIGHLIGHTS_QUERY,
+                tree_sitter_rust::INJECTIONS_QUERY,
+            ),
+            configuration(
+                tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+                "typescript",
+                &typescript,
+                "",
+            ),
+            configuration(
+                tree_sitter_python::LANGUAGE.into(),
+                "python",
+                tree_sitter_python::HIGHLIGHTS_QUERY,
+                "",
+            ),
+            configuration(
+                tree_sitter_go::LANGUAGE.into(),
+                "go",
+                tree_sitter_go::HIGHLIGHTS_QUERY,
+                "",
+            ),
+            configuration(
+                tree_sitter_md::LANGUAGE.into(),
+                "markdown",
+                tree_sitter_md::HIGHLIGHT_QUERY_BLOCK,
+                tree_sitter_md::INJECTION_QUERY_BLOCK,
+            ),
+            configuration(
+                tree_sitter_md::INLINE_LANGUAGE.into(),
+                "markdown_inline",
+                tree_sitter_md::HIGHLIGHT_QUERY_INLINE,
+                tree_sitter_md::INJECTION_QUERY_INLINE,
+            ),
+            configuration(
+                tree_sitter_json::LANGUAGE.into(),
+                "json",
+                tree_sitter_json::HIGHLIGHTS_QUERY,
+                "",
+            ),
+        ];
+
+        let mut names = configurations
+            .iter()
+            .flat_map(|configuration| configuration.query.capture_names())
+            .map(|name| name.to_string())
+            .collect::<Vec<_>>();
+        names.sort();
+        names.dedup();
+        for configuration in &mut configurations {
+            configuration.configure(&names);
+        }
+
+        Self {
+            configurations,
+            names,
+        }
+    }
+
+    fn configuration(&self, language: &str) -> Option<&HighlightConfiguration> {
+        let language = match language {
+            "rs" => "rust",
+            "ts" => "typescript",
+            "py" => "python",
+            "md" => "markdown",
+            language => language,
+        };
+        self.configurations
+            .iter()
+            .find(|configuration| configuration.language_name == language)
+    }
+
+    /// Highlighted lines of `source`. Code blocks in Markdown are highlighted
+    /// in their own language when it is one of the sample languages.
+    pub fn highlight(&self, language: &str, source: &str) -> Vec<Vec<Span>> {
+        let configuration = self
+            .configuration(language)
+            .unwrap_or_else(|| panic!("No grammar for {}", language));
+        let mut highlighter = Highlighter::new();
+        let events = highlighter
+            .highlight(configuration, source.as_bytes(), None, None, |name| {
+                self.configuration(name)
+            })
+            .expect("Error highlighting sample");
+
+        let mut lines = vec![vec![]];
+        let mut stack: Vec<&str> = vec![];
+        for event in events {
+            match event.expect("Error highlighting sample") {
+                HighlightEvent::HighlightStart(highlight) => stack.push(&self.names[highlight.0]),
+                HighlightEvent::HighlightEnd => {
+                    stack.pop();
+                }
+                HighlightEvent::Source { start, end } => {
+                    for (index, text) in source[start..end].split('\n').enumerate() {
+                        if index > 0 {
+                            lines.push(vec![]);
+                        }
+                        if !text.is_empty() {
+                            let capture = stack.last().map(|name| name.to_string());
+                            lines.last_mut().unwrap().push((capture, text.to_string()));
+                        }
+                    }
+                }
+            }
+        }
+
+        if lines.last().is_some_and(Vec::is_empty) {
+            lines.pop();
+        }
+        lines
+    }
+}
+
+impl Default for SampleHighlighter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// One sample as a `<pre>` block on the editor background, each span styled
+/// the way Zed would style its capture. Hovering a span shows the capture name.
+pub fn pre(theme: &Theme, highlighter: &SampleHighlighter, language: &str, source: &str) -> String {
+    let mut body = format!(r#"<pre style="background: {}">"#, theme.background.to_css());
+
+    for line in highlighter.highlight(language, source) {
+        for (capture, text) in line {
+            let Some(capture) = capture else {
+                body.push_str(&escape(&text));
+                continue;
+            };
+            let style = theme.highlight(&capture);
+            let mut css = format!("color: {}", style.color.to_css());
+            if let Some(background) = style.background {
+                let _ = write!(css, "; background: {}", background.to_css());
+            }
+            if style.italic {
+                css.push_str("; font-style: italic");
+            }
+            if style.bold {
+                css.push_str("; font-weight: bold");
+            }
+            let _ = write!(
+                body,
+                r#"<span title="{}" style="{}">{}</span>"#,
+                capture,
+                css,
+                escape(&text)
+            );
+        }
+        body.push('\n');
+    }
+
+    body.push_str("</pre>");
+    body
+}
+
+/// Every sample as a standalone HTML page.
+pub fn html(theme: &Theme, highlighter: &SampleHighlighter) -> String {
+    let mut body = String::new();
+    for (language, source) in SAMPLES {
+        let _ = write!(
+            body,
+            "<h2>{}</h2>{}",
+            language,
+            pre(theme, highlighter, language, source)
+        );
+    }
+
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>{name}</title>
+<style>
+body {{ background: {secondary}; color: {foreground}; font-family: sans-serif; padding: 16px 32px; }}
+h2 {{ font-size: 14px; font-weight: normal; opacity: 0.7; }}
+pre {{ color: {foreground}; font: 14px/20px "JetBrains Mono", Menlo, monospace; padding: 16px; border-radius: 8px; }}
+</style>
+</head>
+<body>
+<h1>{name}</h1>
+{body}
+</body>
+</html>
+"#,
+        name = escape(&theme.name),
+        secondary = theme.secondary_bg().to_css(),
+        foreground = theme.foreground.to_css(),
+        body = body,
+    )
+}
+
+/// Writes `assets/previews/<theme>_syntax.html` under `root`.
+pub fn write(root: &Path, theme: &Theme, highlighter: &SampleHighlighter) -> io::Result<()> {
+    let dir = root.join("assets").join("previews");
+    fs::create_dir_all(&dir)?;
+    fs::write(
+        dir.join(format!("{}_syntax.html", theme.slug())),
+        html(theme, highlighter),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn highlights_every_sample() {
+        let highlighter = SampleHighlighter::new();
+
+        for (language, source) in SAMPLES {
+            let lines = highlighter.highlight(language, source);
+            let text = lines
+                .iter()
+                .map(|line| {
+                    line.iter()
+                        .map(|(_, text)| text.as_str())
+                        .collect::<String>()
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+
+            assert_eq!(text, source.trim_end_matches('\n'), "{}", language);
+            assert!(lines.iter().flatten().any(|(capture, _)| capture.is_some()));
+        }
+    }
+
+    #[test]
+    fn resolves_captures_with_fallback() {
+        let theme = &crate::snowfall().themes[0];
+        let highlighter = SampleHighlighter::new();
+        let spans = highlighter
+            .highlight("rust", SAMPLES[0].1)
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+        let capture = |text: &str| {
+            spans
+                .iter()
+                .find(|(_, span)| span == text)
+                .and_then(|(capture, _)| capture.clone())
+        };
+
+        assert_eq!(capture("{").as_deref(), Some("punctuation.bracket"));
+        assert_eq!(
+            theme.highlight(&capture("{").unwrap()).color,
+            theme.tokens.brackets
+        );
+        assert!(html(theme, &highlighter).contains(r#"title="punctuation.bracket""#));
+    }
+}
@@ -0,0 +1,618 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::Path,
+};
+
+use serde_json::Value;
+
+use crate::{
+    base16,
+    color::Color,
+    dtcg,
+    theme::{
+        Brightness, ThemeCreatePayload, ThemeDerived, ThemeDiagnostic, ThemeGit, ThemeTerminal,
+        ThemeTokens,
+    },
+};
+
+/// Whatever colors could be read from a foreign theme, keyed by the field
+/// names of `ThemeTokens`, `ThemeTerminal`, `ThemeGit` and `ThemeDiagnostic`.
+/// Anything missing is inferred by [`ImportedPalette::complete`].
+#[derive(Default)]
+pub struct ImportedPalette {
+    pub name: Option<String>,
+    pub brightness: Option<Brightness>,
+    pub accent: Option<Color>,
+    pub background: Option<Color>,
+    pub foreground: Option<Color>,
+    pub tokens: HashMap<&'static str, Color>,
+    pub terminal: HashMap<&'static str, Color>,
+    pub git: HashMap<&'static str, Color>,
+    pub diagnostics: HashMap<&'static str, Color>,
+    pub colors: BTreeMap<String, Color>,
+    /// Roles aliased to a named color, see [`ThemeCreatePayload::names`].
+    pub names: BTreeMap<String, String>,
+    pub derived: ThemeDerived,
+}
+
+/// Reads a VS Code color theme (`.json`), a TextMate theme (`.tmTheme`,
+/// `.plist`), a base16/base24 scheme (`.yaml`, `.yml`) or a DTCG design
+/// tokens file (`.tokens.json`, `.tokens`).
+pub fn import(path: &Path) -> Result<ThemeCreatePayload, String> {
+    let source = fs::read_to_string(path)
+        .map_err(|error| format!("Error reading {}: {}", path.display(), error))?;
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default()
+        .to_lowercase();
+
+    let imported = match extension.as_str() {
+        "json" if path.to_string_lossy().ends_with(".tokens.json") => dtcg::import(&source),
+        "tokens" => dtcg::import(&source),
+        "json" | "jsonc" => vscode(&source),
+        #[cfg(feature = "cli")]
+        "tmtheme" | "plist" | "xml" => textmate(&source),
+        "yaml" | "yml" => base16::import(&source),
+        _ => Err(format!("Unknown theme format: {}", path.display())),
+    }
+    .map_err(|error| format!("{}: {}", path.display(), error))?;
+
+    let fallback_name = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("Imported")
+        .to_string();
+    imported.complete(fallback_name)
+}
+
+/// A TextMate-style rule: a scope selector and the color it assigns.
+struct ScopeRule {
+    scopes: Vec<String>,
+    color: Color,
+}
+
+/// Scopes to look for per token role, most specific first.
+const TOKEN_SCOPES: [(&str, &[&str]); 9] = [
+    ("comment", &["comment"]),
+    ("strings", &["string.quoted", "string"]),
+    (
+        "types",
+        &["entity.name.type", "support.type", "storage.type"],
+    ),
+    ("functions", &["entity.name.function", "support.function"]),
+    (
+        "properties",
+        &[
+            "variable.other.property",
+            "support.type.property-name",
+            "meta.object-literal.key",
+            "variable.other.member",
+        ],
+    ),
+    ("keywords", &["keyword.control", "keyword", "storage"]),
+    (
+        "constants",
+        &["constant.numeric", "constant.language", "constant"],
+    ),
+    ("operators", &["keyword.operator"]),
+    (
+        "brackets",
+        &["punctuation.bracket", "meta.brace", "punctuation"],
+    ),
+];
+
+/// Color the most specific rule assigns to the first scope in `scopes` that
+/// any rule matches. A rule for `keyword` matches `keyword.operator`, like
+/// TextMate selectors do.
+fn scope_color(rules: &[ScopeRule], scopes: &[&str]) -> Option<Color> {
+    scopes.iter().find_map(|scope| {
+        rules
+            .iter()
+            .flat_map(|rule| {
+                rule.scopes
+                    .iter()
+                    .map(move |selector| (selector, rule.color))
+            })
+            .filter(|(selector, _)| {
+                *scope == selector.as_str() || scope.starts_with(&format!("{}.", selector))
+            })
+            .max_by_key(|(selector, _)| selector.len())
+            .map(|(_, color)| color)
+    })
+}
+
+fn token_colors(rules: &[ScopeRule], imported: &mut ImportedPalette) {
+    for (role, scopes) in TOKEN_SCOPES {
+        if let Some(color) = scope_color(rules, scopes) {
+            imported.tokens.insert(role, color);
+        }
+    }
+    if let Some(color) = scope_color(rules, &["invalid"]) {
+        imported.diagnostics.insert("error", color);
+    }
+}
+
+/// Strips `//` and `/* */` comments and trailing commas, which VS Code
+/// accepts in theme files but JSON parsers do not.
+fn strip_jsonc(source: &str) -> String {
+    let mut result = String::with_capacity(source.len());
+    let mut chars = source.chars().peekable();
+    let mut in_string = false;
+    // Where the last comma went in `result`, until something other than
+    // whitespace or a comment shows whether it was trailing.
+    let mut comma = None;
+
+    while let Some(c) = chars.next() {
+        if in_string {
+            result.push(c);
+            match c {
+                '\\' => result.extend(chars.next()),
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        match (c, chars.peek()) {
+            ('"', _) => {
+                in_string = true;
+                comma = None;
+                result.push(c);
+            }
+            ('/', Some('/')) => {
+                while chars.peek().is_some_and(|c| *c != '\n') {
+                    chars.next();
+                }
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut previous = ' ';
+                for c in chars.by_ref() {
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+            }
+            (',', _) => {
+                comma = Some(result.len());
+                result.push(c);
+            }
+            ('}' | ']', _) => {
+                if let Some(index) = comma.take() {
+                    result.remove(index);
+                }
+                result.push(c);
+            }
+            _ => {
+                if !c.is_whitespace() {
+                    comma = None;
+                }
+                result.push(c);
+            }
+        }
+    }
+
+    result
+}
+
+/// Reads a VS Code color theme: workbench `colors` plus `tokenColors` rules.
+pub fn vscode(source: &str) -> Result<ImportedPalette, String> {
+    let document: Value =
+        serde_json::from_str(&strip_jsonc(source)).map_err(|error| error.to_string())?;
+    let colors = &document["colors"];
+    let color = |keys: &[&str]| {
+        keys.iter()
+            .find_map(|key| colors[key].as_str().and_then(Color::parse))
+    };
+
+    let mut imported = ImportedPalette {
+        name: document["name"].as_str().map(String::from),
+        brightness: match document["type"].as_str() {
+            Some("light") | Some("hcLight") => Some(Brightness::Light),
+            Some("dark") | Some("hc") | Some("hcDark") => Some(Brightness::Dark),
+            _ => None,
+        },
+        background: color(&["editor.background"]),
+        foreground: color(&["editor.foreground", "foreground"]),
+        accent: color(&["focusBorder", "button.background", "textLink.foreground"]),
+        ..Default::default()
+    };
+
+    for (role, _, bright_role, _) in base16::TERMINAL_SLOTS {
+        for role in [role, bright_role] {
+            let key = format!("terminal.ansi{}", vscode_ansi_name(role));
+            if let Some(color) = color(&[key.as_str()]) {
+                imported.terminal.insert(role, color);
+            }
+        }
+    }
+    for (role, keys) in [
+        (
+            "added",
+            &[
+                "gitDecoration.addedResourceForeground",
+                "editorGutter.addedBackground",
+            ],
+        ),
+        (
+            "modified",
+            &[
+                "gitDecoration.modifiedResourceForeground",
+                "editorGutter.modifiedBackground",
+            ],
+        ),
+        (
+            "removed",
+            &[
+                "gitDecoration.deletedResourceForeground",
+                "editorGutter.deletedBackground",
+            ],
+        ),
+    ] {
+        if let Some(color) = color(keys) {
+            imported.git.insert(role, color);
+        }
+    }
+    for (role, key) in [
+        ("error", "editorError.foreground"),
+        ("warning", "editorWarning.foreground"),
+        ("info", "editorInfo.foreground"),
+    ] {
+        if let Some(color) = color(&[key]) {
+            imported.diagnostics.insert(role, color);
+        }
+    }
+
+    let rules = document["tokenColors"]
+        .as_array()
+        .map(|rules| {
+            rules
+                .iter()
+                .filter_map(|rule| {
+                    let color = rule["settings"]["foreground"]
+                        .as_str()
+                        .and_then(Color::parse)?;
+                    let scopes = match &rule["scope"] {
+                        Value::String(scopes) => split_scopes(scopes),
+                        Value::Array(scopes) => scopes
+                            .iter()
+                            .filter_map(Value::as_str)
+                            .flat_map(split_scopes)
+                            .collect(),
+                        _ => return None,
+                    };
+                    Some(ScopeRule { scopes, color })
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    token_colors(&rules, &mut imported);
+
+    Ok(imported)
+}
+
+/// `bright_red` -> `BrightRed`, as in `terminal.ansiBrightRed`.
+fn vscode_ansi_name(role: &str) -> String {
+    role.split('_')
+        .map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+/// Splits `"comment, string.quoted"` into selectors. Descendant selectors
+/// like `source.js string` are reduced to their last scope.
+fn split_scopes(scopes: &str) -> Vec<String> {
+    scopes
+        .split(',')
+        .filter_map(|scope| scope.split_whitespace().last())
+        .map(String::from)
+        .collect()
+}
+
+/// Reads a TextMate `.tmTheme` property list: global `settings` plus scoped rules.
+#[cfg(feature = "cli")]
+pub fn textmate(source: &str) -> Result<ImportedPalette, String> {
+    let document =
+        plist::Value::from_reader_xml(source.as_bytes()).map_err(|error| error.to_string())?;
+    let document = document
+        .as_dictionary()
+        .ok_or("Expected a property list dictionary")?;
+    let settings = document
+        .get("settings")
+        .and_then(plist::Value::as_array)
+        .ok_or("Missing settings array")?;
+
+    let string = |value: &plist::Value, key: &str| {
+        value
+            .as_dictionary()
+            .and_then(|dictionary| dictionary.get(key))
+            .and_then(plist::Value::as_string)
+            .map(String::from)
+    };
+    let global = settings
+        .iter()
+        .find(|entry| string(entry, "scope").is_none())
+        .and_then(|entry| entry.as_dictionary()?.get("settings"));
+    let global_color = |key: &str| {
+        global
+            .and_then(|global| string(global, key))
+            .and_then(|hex| Color::parse(&hex))
+    };
+
+    let mut imported = ImportedPalette {
+        name: document
+            .get("name")
+            .and_then(plist::Value::as_string)
+            .map(String::from),
+        background: global_color("background"),
+        foreground: global_color("foreground"),
+        accent: global_color("caret").or_else(|| global_color("selectionBorder")),
+        ..Default::default()
+    };
+
+    let rules = settings
+        .iter()
+        .filter_map(|entry| {
+            let scopes = split_scopes(&string(entry, "scope")?);
+            let rule = entry.as_dictionary()?.get("settings")?;
+            let color = Color::parse(&string(rule, "foreground")?)?;
+            Some(ScopeRule { scopes, color })
+        })
+        .collect::<Vec<_>>();
+    token_colors(&rules, &mut imported);
+
+    if let Some(color) = scope_color(&rules, &["markup.inserted", "markup.inserted.diff"]) {
+        imported.git.insert("added", color);
+    }
+    if let Some(color) = scope_color(&rules, &["markup.changed"]) {
+        imported.git.insert("modified", color);
+    }
+    if let Some(color) = scope_color(&rules, &["markup.deleted"]) {
+        imported.git.insert("removed", color);
+    }
+
+    Ok(imported)
+}
+
+impl ImportedPalette {
+    /// Fills in everything the source did not specify, deriving it from the
+    /// colors it did. Only the background and foreground are required.
+    pub fn complete(self, fallback_name: String) -> Result<ThemeCreatePayload, String> {
+        let background = self.background.ok_or("Could not find a background color")?;
+        let foreground = self.foreground.ok_or("Could not find a foreground color")?;
+        let brightness = self
+            .brightness
+            .unwrap_or_else(|| match background.to_oklch().0 < 0.5 {
+                true => Brightness::Dark,
+                false => Brightness::Light,
+            });
+        let white = Color::from_hex("#ffffff");
+
+        let token = |role: &str| self.tokens.get(role).copied();
+        let functions = token("functions").unwrap_or(foreground);
+        let accent = self.accent.unwrap_or(functions);
+        let tokens = ThemeTokens {
+            brackets: token("brackets").unwrap_or(functions),
+            comment: token("comment").unwrap_or(foreground.mix(background, 0.5)),
+            strings: token("strings").unwrap_or(foreground),
+            types: token("types").unwrap_or(foreground),
+            functions,
+            properties: token("properties").unwrap_or(foreground),
+            keywords: token("keywords").unwrap_or(foreground),
+            constants: token("constants").unwrap_or(foreground),
+            operators: token("operators").unwrap_or(foreground),
+        };
+
+        let error = self
+            .diagnostics
+            .get("error")
+            .or(self.terminal.get("red"))
+            .or(self.git.get("removed"))
+            .copied()
+            .unwrap_or(Color::from_hex("#fa7583"));
+        let ansi =
+            |role: &str, fallback: Color| self.terminal.get(role).copied().unwrap_or(fallback);
+        let red = ansi("red", error);
+        let green = ansi(
+            "green",
+            self.git.get("added").copied().unwrap_or(tokens.strings),
+        );
+        let yellow = ansi("yellow", tokens.constants);
+        let blue = ansi("blue", functions);
+        let magenta = ansi("magenta", tokens.keywords);
+        let cyan = ansi("cyan", tokens.types);
+        let (black_base, white_base) = match brightness {
+            Brightness::Dark => (background.darken(0.2), foreground),
+            Brightness::Light => (foreground.darken(0.5), background.darken(0.1)),
+        };
+        let black_color = ansi("black", black_base);
+        let white_color = ansi("white", white_base);
+        let bright = |role: &str, base: Color| ansi(role, base.mix(white, 0.2));
+        let terminal = ThemeTerminal {
+            black: black_color,
+            red,
+            green,
+            yellow,
+            blue,
+            magenta,
+            cyan,
+            white: white_color,
+            bright_black: ansi("bright_black", black_color.mix(white, 0.3)),
+            bright_red: bright("bright_red", red),
+            bright_green: bright("bright_green", green),
+            bright_yellow: bright("bright_yellow", yellow),
+            bright_blue: bright("bright_blue", blue),
+            bright_magenta: bright("bright_magenta", magenta),
+            bright_cyan: bright("bright_cyan", cyan),
+            bright_white: ansi("bright_white", white_color.mix(white, 0.5)),
+        };
+
+        let git = ThemeGit {
+            added: self.git.get("added").copied().unwrap_or(green),
+            modified: self.git.get("modified").copied().unwrap_or(accent),
+            removed: self.git.get("removed").copied().unwrap_or(red),
+        };
+        let diagnostics = ThemeDiagnostic {
+            error,
+            warning: self.diagnostics.get("warning").copied().unwrap_or(yellow),
+            info: self.diagnostics.get("info").copied().unwrap_or(blue),
+        };
+
+        Ok(ThemeCreatePayload {
+            name: self.name.unwrap_or(fallback_name),
+            brightness,
+            accent,
+            background,
+            foreground,
+            tokens,
+            terminal,
+            diagnostics,
+            git,
+            colors: self.colors,
+            names: self.names,
+            derived: self.derived,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_vscode_themes_with_comments() {
+        let source = r##"{
+            // Exported from VS Code
+            "name": "Frost",
+            "type": "dark",
+            "colors": {
+                "editor.background": "#24272d",
+                "editor.foreground": "#91a2a6",
+                "focusBorder": "#84c4df", /* accent */
+                "terminal.ansiBrightRed": "#a04041",
+                "gitDecoration.deletedResourceForeground": "#fa7583", // last
+            },
+            "tokenColors": [
+                { "scope": "comment", "settings": { "foreground": "#474c54" } },
+                { "scope": ["keyword", "storage"], "settings": { "foreground": "#b08cba" } },
+                { "scope": "keyword.operator", "settings": { "foreground": "#acbdc3" } },
+                { "scope": "string, string.quoted", "settings": { "foreground": "#bdb969" } }, // last
+            ],
+            "semanticHighlighting": true, /* trailing */
+        }"##;
+        let payload = vscode(source).unwrap().complete("fallback".into()).unwrap();
+
+        assert_eq!(payload.name, "Frost");
+        assert_eq!(payload.brightness, Brightness::Dark);
+        assert_eq!(payload.accent, Color::from_hex("#84c4df"));
+        assert_eq!(payload.tokens.comment, Color::from_hex("#474c54"));
+        assert_eq!(payload.tokens.keywords, Color::from_hex("#b08cba"));
+        assert_eq!(payload.tokens.operators, Color::from_hex("#acbdc3"));
+        assert_eq!(payload.tokens.strings, Color::from_hex("#bdb969"));
+        assert_eq!(payload.terminal.bright_red, Color::from_hex("#a04041"));
+        assert_eq!(payload.git.removed, Color::from_hex("#fa7583"));
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn reads_textmate_themes() {
+        let source = r##"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>name</key><string>Frost</string>
+    <key>settings</key>
+    <array>
+        <dict>
+            <key>settings</key>
+            <dict>
+                <key>background</key><string>#FFFFFF</string>
+                <key>foreground</key><string>#5C6165</string>
+                <key>caret</key><string>#88C0D0</string>
+            </dict>
+        </dict>
+        <dict>
+            <key>scope</key><string>entity.name.function, support.function</string>
+            <key>settings</key><dict><key>foreground</key><string>#50afce</string></dict>
+        </dict>
+        <dict>
+            <key>scope</key><string>markup.inserted</string>
+            <key>settings</key><dict><key>foreground</key><string>#8ACB6B</string></dict>
+        </dict>
+    </array>
+</dict>
+</plist>"##;
+        let payload = textmate(source)
+            .unwrap()
+            .complete("fallback".into())
+            .unwrap();
+
+        assert_eq!(payload.brightness, Brightness::Light);
+        assert_eq!(payload.accent, Color::from_hex("#88c0d0"));
+        assert_eq!(payload.tokens.functions, Color::from_hex("#50afce"));
+        assert_eq!(payload.tokens.brackets, Color::from_hex("#50afce"));
+        assert_eq!(payload.git.added, Color::from_hex("#8acb6b"));
+    }
+
+    #[test]
+    fn reads_base16_and_base24_schemes() {
+        let classic = r#"
+scheme: "Snowfall"
+author: "freethinkel"
+base00: "24272d"
+base03: "474c54"
+base05: "91a2a6"
+base08: "fa7583"
+base0B: "bdb969"
+base0D: "7fb2c7"
+base0E: "b08cba"
+"#;
+        let payload = base16::import(classic)
+            .unwrap()
+            .complete("fallback".into())
+            .unwrap();
+
+        assert_eq!(payload.name, "Snowfall");
+        assert_eq!(payload.brightness, Brightness::Dark);
+        assert_eq!(payload.tokens.comment, Color::from_hex("#474c54"));
+        assert_eq!(payload.tokens.strings, Color::from_hex("#bdb969"));
+        assert_eq!(payload.terminal.red, Color::from_hex("#fa7583"));
+        assert_eq!(payload.terminal.bright_black, Color::from_hex("#474c54"));
+
+        let base24 = r##"
+system: "base24"
+name: "Snowfall"
+variant: "light"
+palette:
+  base00: "#ffffff"
+  base05: "#5c6165"
+  base08: "#cc6666"
+  base12: "#a04041"
+"##;
+        let payload = base16::import(base24)
+            .unwrap()
+            .complete("fallback".into())
+            .unwrap();
+
+        assert_eq!(payload.brightness, Brightness::Light);
+        assert_eq!(payload.terminal.bright_red, Color::from_hex("#a04041"));
+    }
+
+    #[test]
+    fn requires_background_and_foreground() {
+        let error = vscode(r#"{ "colors": {} }"#)
+            .unwrap()
+            .complete("fallback".into())
+            .err();
+
+        assert_eq!(error.as_deref(), Some("Could not find a background color"));
+    }
+}
@@ -0,0 +1,375 @@
+use std::{fmt::Write, path::Path};
+
+use serde_json::json;
+
+use crate::{
+    color::Color,
+    docs::style,
+    export::{escape, OutputFile, ThemeExporter},
+    theme::{Brightness, Theme, ThemePackage},
+};
+
+/// JetBrains language-default attributes and the Zed capture whose style
+/// they take. Languages inherit from these unless a scheme overrides them.
+const ATTRIBUTES: [(&str, &str); 32] = [
+    ("DEFAULT_KEYWORD", "keyword"),
+    ("DEFAULT_STRING", "string"),
+    ("DEFAULT_VALID_STRING_ESCAPE", "string.escape"),
+    ("DEFAULT_NUMBER", "number"),
+    ("DEFAULT_CONSTANT", "boolean"),
+    ("DEFAULT_LINE_COMMENT", "comment"),
+    ("DEFAULT_BLOCK_COMMENT", "comment"),
+    ("DEFAULT_DOC_COMMENT", "comment.doc"),
+    ("DEFAULT_DOC_COMMENT_TAG", "comment.doc"),
+    ("DEFAULT_DOC_MARKUP", "comment.doc"),
+    ("DEFAULT_FUNCTION_DECLARATION", "function"),
+    ("DEFAULT_FUNCTION_CALL", "function"),
+    ("DEFAULT_INSTANCE_METHOD", "function"),
+    ("DEFAULT_STATIC_METHOD", "function"),
+    ("DEFAULT_CLASS_NAME", "type"),
+    ("DEFAULT_CLASS_REFERENCE", "type"),
+    ("DEFAULT_INTERFACE_NAME", "type"),
+    ("DEFAULT_METADATA", "attribute"),
+    ("DEFAULT_IDENTIFIER", "variable"),
+    ("DEFAULT_LOCAL_VARIABLE", "variable"),
+    ("DEFAULT_PARAMETER", "variable"),
+    ("DEFAULT_INSTANCE_FIELD", "property"),
+    ("DEFAULT_STATIC_FIELD", "constant"),
+    ("DEFAULT_GLOBAL_VARIABLE", "variable.special"),
+    ("DEFAULT_PREDEFINED_SYMBOL", "variable.special"),
+    ("DEFAULT_LABEL", "label"),
+    ("DEFAULT_OPERATION_SIGN", "operator"),
+    ("DEFAULT_BRACES", "punctuation.bracket"),
+    ("DEFAULT_BRACKETS", "punctuation.bracket"),
+    ("DEFAULT_PARENTHS", "punctuation.bracket"),
+    ("DEFAULT_COMMA", "punctuation.delimiter"),
+    ("DEFAULT_TAG", "tag"),
+];
+
+/// `RRGGBB`, composited over the background since schemes have no alpha.
+fn hex(theme: &Theme, color: Color) -> String {
+    color.over(theme.background).to_hex()[1..].to_string()
+}
+
+fn option(xml: &mut String, indent: &str, name: &str, value: &str) {
+    let _ = writeln!(
+        xml,
+        "{}<option name=\"{}\" value=\"{}\" />",
+        indent, name, value
+    );
+}
+
+/// An `<option>` holding a text attribute, from `FOREGROUND`, `BACKGROUND`,
+/// `FONT_TYPE`, `EFFECT_COLOR`, `EFFECT_TYPE` or `ERROR_STRIPE_COLOR` values.
+fn attribute(xml: &mut String, name: &str, values: &[(&str, String)]) {
+    let _ = writeln!(xml, "    <option name=\"{}\">\n      <value>", name);
+    for (key, value) in values {
+        option(xml, "        ", key, value);
+    }
+    xml.push_str("      </value>\n    </option>\n");
+}
+
+/// An editor color scheme: the language defaults from the tokens, the
+/// editor colors, the git gutter, file status and diff colors, and the
+/// diagnostics with their error stripe marks.
+pub fn scheme(theme: &Theme) -> String {
+    let color = |color: Color| hex(theme, color);
+    let parent = match theme.brightness {
+        Brightness::Dark => "Darcula",
+        Brightness::Light => "Default",
+    };
+    let mut xml = format!(
+        "<!-- {}, generated by snowfall-zed -->\n\
+         <scheme name=\"{}\" version=\"142\" parent_scheme=\"{}\">\n  <colors>\n",
+        escape(&theme.name),
+        escape(&theme.name),
+        parent
+    );
+
+    let selection = color(theme.accent.with_opacity(0.2));
+    let colors = [
+        ("CARET_COLOR", color(theme.accent)),
+        ("CARET_ROW_COLOR", color(theme.secondary_bg())),
+        ("CONSOLE_BACKGROUND_KEY", color(theme.background)),
+        ("GUTTER_BACKGROUND", color(theme.background)),
+        ("INDENT_GUIDE", color(theme.border())),
+        (
+            "SELECTED_INDENT_GUIDE",
+            color(theme.foreground.with_opacity(0.4)),
+        ),
+        (
+            "LINE_NUMBERS_COLOR",
+            color(theme.foreground.with_opacity(0.4)),
+        ),
+        ("LINE_NUMBER_ON_CARET_ROW_COLOR", color(theme.foreground)),
+        ("RIGHT_MARGIN_COLOR", color(theme.border())),
+        ("SELECTION_BACKGROUND", selection.clone()),
+        ("TEARLINE_COLOR", color(theme.border())),
+        ("WHITESPACES", color(theme.border())),
+        ("ADDED_LINES_COLOR", color(theme.git.added)),
+        ("MODIFIED_LINES_COLOR", color(theme.git.modified)),
+        ("DELETED_LINES_COLOR", color(theme.git.removed)),
+        ("FILESTATUS_ADDED", color(theme.git.added)),
+        ("FILESTATUS_MODIFIED", color(theme.git.modified)),
+        ("FILESTATUS_DELETED", color(theme.git.removed)),
+    ];
+    for (name, value) in colors {
+        option(&mut xml, "    ", name, &value);
+    }
+    xml.push_str("  </colors>\n  <attributes>\n");
+
+    attribute(
+        &mut xml,
+        "TEXT",
+        &[
+            ("FOREGROUND", color(theme.foreground)),
+            ("BACKGROUND", color(theme.background)),
+        ],
+    );
+    for (name, capture) in ATTRIBUTES {
+        let style = style(theme, capture);
+        let mut values = vec![("FOREGROUND", color(style.color))];
+        if let Some(background) = style.background {
+            values.push(("BACKGROUND", color(background)));
+        }
+        let font_type = style.bold as u8 | (style.italic as u8) << 1;
+        if font_type != 0 {
+            values.push(("FONT_TYPE", font_type.to_string()));
+        }
+        attribute(&mut xml, name, &values);
+    }
+
+    // Diff backgrounds are tinted, their stripe marks take the full color.
+    let diffs = [
+        ("DIFF_INSERTED", theme.git.added),
+        ("DIFF_MODIFIED", theme.git.modified),
+        ("DIFF_DELETED", theme.git.removed),
+    ];
+    for (name, git) in diffs {
+        attribute(
+            &mut xml,
+            name,
+            &[
+                ("BACKGROUND", color(git.with_opacity(0.15))),
+                ("ERROR_STRIPE_COLOR", color(git)),
+            ],
+        );
+    }
+
+    // EFFECT_TYPE 2 is a wavy underline.
+    let diagnostics = [
+        ("ERRORS_ATTRIBUTES", theme.diagnostics.error),
+        ("WARNING_ATTRIBUTES", theme.diagnostics.warning),
+        ("WEAK_WARNING_ATTRIBUTES", theme.diagnostics.info),
+        ("INFO_ATTRIBUTES", theme.diagnostics.info),
+    ];
+    for (name, diagnostic) in diagnostics {
+        attribute(
+            &mut xml,
+            name,
+            &[
+                ("EFFECT_COLOR", color(diagnostic)),
+                ("EFFECT_TYPE", "2".to_string()),
+                ("ERROR_STRIPE_COLOR", color(diagnostic)),
+            ],
+        );
+    }
+    attribute(
+        &mut xml,
+        "WRONG_REFERENCES_ATTRIBUTES",
+        &[
+            ("FOREGROUND", color(theme.diagnostics.error)),
+            ("ERROR_STRIPE_COLOR", color(theme.diagnostics.error)),
+        ],
+    );
+
+    let search = [
+        "SEARCH_RESULT_ATTRIBUTES",
+        "TEXT_SEARCH_RESULT_ATTRIBUTES",
+        "IDENTIFIER_UNDER_CARET_ATTRIBUTES",
+    ];
+    for name in search {
+        attribute(
+            &mut xml,
+            name,
+            &[
+                ("BACKGROUND", selection.clone()),
+                ("ERROR_STRIPE_COLOR", color(theme.accent)),
+            ],
+        );
+    }
+
+    xml.push_str("  </attributes>\n</scheme>\n");
+    xml
+}
+
+/// A UI theme for the IDE around the editor: surfaces from `secondary_bg`,
+/// separators from `border`, and the scheme as its `editorScheme`.
+pub fn ui_theme(package: &ThemePackage, theme: &Theme) -> String {
+    let color = |color: Color| color.over(theme.background).to_hex();
+    let document = json!({
+        "name": theme.name,
+        "dark": theme.brightness == Brightness::Dark,
+        "author": package.author,
+        "editorScheme": format!("/{}.icls", theme.slug()),
+        "colors": {
+            "background": color(theme.background),
+            "foreground": color(theme.foreground),
+            "secondaryBackground": color(theme.secondary_bg()),
+            "border": color(theme.border()),
+            "accent": color(theme.accent),
+            "selection": color(theme.accent.with_opacity(0.2)),
+            "muted": color(theme.foreground.with_opacity(0.5)),
+            "error": color(theme.diagnostics.error),
+            "warning": color(theme.diagnostics.warning),
+        },
+        "ui": {
+            "*": {
+                "background": "secondaryBackground",
+                "foreground": "foreground",
+                "infoForeground": "muted",
+                "disabledForeground": "muted",
+                "selectionBackground": "selection",
+                "selectionForeground": "foreground",
+                "selectionInactiveBackground": "selection",
+                "borderColor": "border",
+                "separatorColor": "border",
+                "focusColor": "accent",
+                "focusedBorderColor": "accent",
+                "errorForeground": "error",
+                "warningForeground": "warning",
+            },
+            "Editor": { "background": "background" },
+            "EditorTabs": {
+                "background": "secondaryBackground",
+                "underlinedTabBackground": "background",
+                "underlineColor": "accent",
+            },
+            "Link": { "activeForeground": "accent" },
+            "Button": {
+                "default": {
+                    "startBackground": "accent",
+                    "endBackground": "accent",
+                    "foreground": "background",
+                },
+            },
+            "StatusBar": { "borderColor": "border" },
+            "ToolWindow": { "Header": { "background": "secondaryBackground" } },
+        },
+    });
+    serde_json::to_string_pretty(&document).unwrap() + "\n"
+}
+
+/// Writes one editor color scheme per theme to `dist/jetbrains/<theme>.icls`.
+pub struct JetBrainsExporter;
+
+impl ThemeExporter for JetBrainsExporter {
+    fn name(&self) -> &str {
+        "jetbrains"
+    }
+
+    fn extension(&self) -> &str {
+        "icls"
+    }
+
+    fn export(&self, package: &ThemePackage) -> Vec<OutputFile> {
+        package
+            .themes
+            .iter()
+            .map(|theme| OutputFile {
+                path: Path::new("dist")
+                    .join("jetbrains")
+                    .join(format!("{}.icls", theme.slug())),
+                contents: scheme(theme),
+            })
+            .collect()
+    }
+}
+
+/// Writes the UI theme that goes with each scheme to
+/// `dist/jetbrains/<theme>.theme.json`.
+pub struct JetBrainsUiExporter;
+
+impl ThemeExporter for JetBrainsUiExporter {
+    fn name(&self) -> &str {
+        "jetbrains-ui"
+    }
+
+    fn extension(&self) -> &str {
+        "json"
+    }
+
+    fn export(&self, package: &ThemePackage) -> Vec<OutputFile> {
+        package
+            .themes
+            .iter()
+            .map(|theme| OutputFile {
+                path: Path::new("dist")
+                    .join("jetbrains")
+                    .join(format!("{}.theme.json", theme.slug())),
+                contents: ui_theme(package, theme),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_tokens_git_and_diagnostics() {
+        let package = crate::snowfall();
+        let theme = &package.themes[0];
+        let xml = scheme(theme);
+        let value = |name: &str, color: Color| {
+            format!(
+                "<option name=\"{}\" value=\"{}\" />",
+                name,
+                &color.over(theme.background).to_hex()[1..]
+            )
+        };
+
+        assert!(xml.contains("parent_scheme=\"Darcula\""));
+        assert!(xml.contains(&format!(
+            "<option name=\"DEFAULT_KEYWORD\">\n      <value>\n        {}\n",
+            value("FOREGROUND", theme.tokens.keywords)
+        )));
+        assert!(xml.contains(&format!(
+            "<option name=\"DEFAULT_CLASS_NAME\">\n      <value>\n        {}\n        <option name=\"FONT_TYPE\" value=\"1\" />",
+            value("FOREGROUND", theme.tokens.types)
+        )));
+        assert!(xml.contains(&value("ADDED_LINES_COLOR", theme.git.added)));
+        assert!(xml.contains(&format!(
+            "<option name=\"ERRORS_ATTRIBUTES\">\n      <value>\n        {}\n        <option name=\"EFFECT_TYPE\" value=\"2\" />\n        {}\n",
+            value("EFFECT_COLOR", theme.diagnostics.error),
+            value("ERROR_STRIPE_COLOR", theme.diagnostics.error)
+        )));
+        let light = package
+            .themes
+            .iter()
+            .find(|theme| theme.brightness == Brightness::Light)
+            .unwrap();
+        assert!(scheme(light).contains("parent_scheme=\"Default\""));
+    }
+
+    #[test]
+    fn ui_theme_points_at_the_scheme() {
+        let package = crate::snowfall();
+        for theme in &package.themes {
+            let document =
+                serde_json::from_str::<serde_json::Value>(&ui_theme(&package, theme)).unwrap();
+
+            assert_eq!(document["editorScheme"], format!("/{}.icls", theme.slug()));
+            assert_eq!(document["dark"], theme.brightness == Brightness::Dark);
+            assert_eq!(
+                document["colors"]["secondaryBackground"],
+                theme.secondary_bg().over(theme.background).to_hex()
+            );
+            assert_eq!(
+                document["colors"]["border"],
+                theme.border().over(theme.background).to_hex()
+            );
+        }
+    }
+}
@@ -0,0 +1,192 @@
+use std::{fmt::Write, path::Path};
+
+use crate::{
+    builder::SyntaxStyle,
+    color::Color,
+    docs::style,
+    export::{OutputFile, ThemeExporter},
+    theme::{Theme, ThemePackage},
+};
+
+/// Kakoune's code and markup faces and the Zed capture whose style they take.
+const FACES: [(&str, &str); 20] = [
+    ("value", "number"),
+    ("type", "type"),
+    ("variable", "variable"),
+    ("module", "type"),
+    ("function", "function"),
+    ("string", "string"),
+    ("keyword", "keyword"),
+    ("operator", "operator"),
+    ("attribute", "attribute"),
+    ("comment", "comment"),
+    ("documentation", "comment.doc"),
+    ("meta", "attribute"),
+    ("builtin", "variable.special"),
+    ("title", "title"),
+    ("header", "title"),
+    ("mono", "text.literal"),
+    ("block", "text.literal"),
+    ("link", "link_uri"),
+    ("bullet", "punctuation.list_marker"),
+    ("list", "punctuation.list_marker"),
+];
+
+/// `rgb:RRGGBB`, composited over the background so faces look the same on
+/// terminals that ignore alpha.
+fn rgb(theme: &Theme, color: Color) -> String {
+    format!("rgb:{}", &color.over(theme.background).to_hex()[1..])
+}
+
+fn face(theme: &Theme, style: &SyntaxStyle) -> String {
+    let mut face = rgb(theme, style.color);
+    if let Some(background) = style.background {
+        face.push(',');
+        face.push_str(&rgb(theme, background));
+    }
+    let attributes = [("b", style.bold), ("i", style.italic)]
+        .into_iter()
+        .filter(|(_, on)| *on)
+        .map(|(attribute, _)| attribute)
+        .collect::<String>();
+    if !attributes.is_empty() {
+        face.push('+');
+        face.push_str(&attributes);
+    }
+    face
+}
+
+/// A Kakoune colorscheme: `set-face` commands for the code, markup, UI and
+/// diagnostic faces.
+pub fn colorscheme(theme: &Theme) -> String {
+    let mut kak = format!("# {}, generated by snowfall-zed\n\n# Code\n", theme.name);
+    for (name, capture) in FACES {
+        let _ = writeln!(
+            kak,
+            "set-face global {} {}",
+            name,
+            face(theme, &style(theme, capture))
+        );
+    }
+
+    let color = |color: Color| rgb(theme, color);
+    let background = color(theme.background);
+    let foreground = color(theme.foreground);
+    let secondary_bg = color(theme.secondary_bg());
+    let border = color(theme.border());
+    let accent = color(theme.accent);
+    let selection = color(theme.accent.with_opacity(0.2));
+    let muted = color(theme.foreground.with_opacity(0.5));
+    let line_number = color(theme.foreground.with_opacity(0.4));
+
+    let ui = [
+        ("Default", format!("{},{}", foreground, background)),
+        ("PrimarySelection", format!("default,{}", selection)),
+        ("SecondarySelection", format!("default,{}", secondary_bg)),
+        ("PrimaryCursor", format!("{},{}", background, accent)),
+        ("SecondaryCursor", format!("{},{}", background, muted)),
+        ("PrimaryCursorEol", format!("{},{}", background, accent)),
+        ("SecondaryCursorEol", format!("{},{}", background, muted)),
+        ("LineNumbers", format!("{},{}", line_number, background)),
+        ("LineNumberCursor", format!("{},{}", foreground, background)),
+        ("LineNumbersWrapped", format!("{},{}", border, background)),
+        ("MenuForeground", format!("{},{}", background, accent)),
+        ("MenuBackground", format!("{},{}", foreground, secondary_bg)),
+        ("MenuInfo", muted.clone()),
+        ("Information", format!("{},{}", foreground, secondary_bg)),
+        (
+            "Error",
+            format!("{},default", color(theme.diagnostics.error)),
+        ),
+        ("StatusLine", format!("{},{}", foreground, secondary_bg)),
+        ("StatusLineMode", format!("{}+b", accent)),
+        ("StatusLineInfo", muted.clone()),
+        ("StatusLineValue", color(theme.diagnostics.warning)),
+        ("StatusCursor", format!("{},{}", background, accent)),
+        ("Prompt", accent.clone()),
+        ("MatchingChar", format!("default,{}+b", selection)),
+        ("Whitespace", border.clone()),
+        ("BufferPadding", border),
+    ];
+    kak.push_str("\n# Interface\n");
+    for (name, face) in ui {
+        let _ = writeln!(kak, "set-face global {} {}", name, face);
+    }
+
+    // Faces read by the language server client, kak-lsp.
+    let diagnostics = [
+        ("DiagnosticError", theme.diagnostics.error),
+        ("DiagnosticWarning", theme.diagnostics.warning),
+        ("DiagnosticInfo", theme.diagnostics.info),
+        ("DiagnosticHint", theme.foreground.with_opacity(0.5)),
+    ];
+    kak.push_str("\n# Diagnostics\n");
+    for (name, diagnostic) in diagnostics {
+        let _ = writeln!(kak, "set-face global {} {}", name, color(diagnostic));
+    }
+    kak
+}
+
+/// Writes one Kakoune colorscheme per theme to `dist/kakoune/<theme>.kak`,
+/// ready to copy into `~/.config/kak/colors`.
+pub struct KakouneExporter;
+
+impl ThemeExporter for KakouneExporter {
+    fn name(&self) -> &str {
+        "kakoune"
+    }
+
+    fn extension(&self) -> &str {
+        "kak"
+    }
+
+    fn export(&self, package: &ThemePackage) -> Vec<OutputFile> {
+        package
+            .themes
+            .iter()
+            .map(|theme| OutputFile {
+                path: Path::new("dist")
+                    .join("kakoune")
+                    .join(format!("{}.kak", theme.slug())),
+                contents: colorscheme(theme),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sets_faces_from_the_palette() {
+        let package = crate::snowfall();
+        let theme = &package.themes[0];
+        let kak = colorscheme(theme);
+        let hex = |color: Color| color.over(theme.background).to_hex()[1..].to_string();
+
+        assert!(kak.contains(&format!(
+            "set-face global keyword rgb:{}\n",
+            hex(theme.tokens.keywords)
+        )));
+        assert!(kak.contains(&format!(
+            "set-face global type rgb:{}+b\n",
+            hex(theme.tokens.types)
+        )));
+        assert!(kak.contains(&format!(
+            "set-face global Default rgb:{},rgb:{}\n",
+            hex(theme.foreground),
+            hex(theme.background)
+        )));
+        assert!(kak
+            .lines()
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .all(|line| line.starts_with("set-face global ")));
+
+        let background = &package.themes[1];
+        assert!(colorscheme(background).contains(&format!(
+            "set-face global string rgb:{},rgb:",
+            &background.tokens.strings.to_hex()[1..]
+        )));
+    }
+}
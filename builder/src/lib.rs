@@ -0,0 +1,92 @@
+#![recursion_limit = "2000"]
+//! The engine behind the Snowfall themes: a small theme model, the color math
+//! it is built on, the Zed serializer and every importer, exporter and report
+//! the `snowfall-zed` binary offers.
+//!
+//! A theme family is a [`ThemePackage`] of [`Theme`]s, each created from a
+//! [`ThemeCreatePayload`]:
+//!
+//! ```
+//! use snowfall_theme::{builder::ZedExporter, color::Color, generate, theme::{Theme, ThemePackage}};
+//!
+//! let seeds = generate::Seeds::new(
+//!     "Company dark".into(),
+//!     Color::from_hex("#1e2024"),
+//!     Color::from_hex("#b4bcc4"),
+//!     Color::from_hex("#e0a458"),
+//! );
+//! let package = ThemePackage {
+//!     name: "Company".into(),
+//!     author: "Company".into(),
+//!     themes: vec![Theme::new(generate::generate(&seeds).payload).unwrap()],
+//! };
+//! let json = ZedExporter::json(&package);
+//! assert!(json.contains("\"Company dark\""));
+//! ```
+
+/// base16 and base24 scheme import and export.
+pub mod base16;
+/// The Zed theme serializer, syntax capture resolution and the `Builder`
+/// that runs exporters.
+pub mod builder;
+/// sRGB colors with hex parsing, mixing, contrast and OKLCH/CIELAB math.
+pub mod color;
+/// Color vision deficiency simulation, collapse reports and safe variants.
+pub mod colorblind;
+/// CSS custom properties and a Tailwind preset for web UIs.
+pub mod css;
+/// Semantic diffs between two builds of a theme family.
+pub mod diff;
+/// Syntax styles for documentation highlighters: Pygments, Chroma,
+/// highlight.js and Shiki.
+pub mod docs;
+/// W3C Design Tokens (DTCG) export and import.
+pub mod dtcg;
+/// The `ThemeExporter` trait and the registry of output formats.
+pub mod export;
+/// Color expressions (`mix(accent, background, 0.9)`) and palettes of named
+/// colors defined by them.
+pub mod expr;
+/// The offline HTML gallery of every style key.
+#[cfg(feature = "cli")]
+pub mod gallery;
+/// Full palettes generated from a few seed colors.
+pub mod generate;
+/// Pairwise distances between token roles.
+pub mod harmony;
+/// Helix themes.
+pub mod helix;
+/// Tree-sitter highlighting of the bundled sample files.
+#[cfg(feature = "cli")]
+pub mod highlight;
+/// VS Code, TextMate and base16 theme import.
+pub mod import;
+/// JetBrains editor color schemes and UI themes.
+pub mod jetbrains;
+/// Kakoune colorschemes.
+pub mod kakoune;
+/// Mock Zed window rendered from serialized styles.
+#[cfg(feature = "cli")]
+pub mod mockup;
+/// SVG/PNG palette previews.
+#[cfg(feature = "cli")]
+pub mod preview;
+/// Hand-highlighted code samples.
+pub mod sample;
+/// 24-bit ANSI terminal previews.
+pub mod show;
+/// The built-in Snowfall themes.
+pub mod snowfall;
+/// Palette spec files: `ThemeCreatePayload` as TOML.
+pub mod spec;
+/// User templates (Tera syntax) as an exporter.
+#[cfg(feature = "cli")]
+pub mod template;
+/// The theme model.
+pub mod theme;
+/// Zed theme JSON parsing back into the model.
+pub mod zed;
+
+pub use color::Color;
+pub use snowfall::{seasons, snowfall};
+pub use theme::{Theme, ThemeCreatePayload, ThemePackage};
@@ -1,120 +1,299 @@
-#![recursion_limit = "2000"]
-
-use builder::Builder;
-use color::Color;
-use theme::{
-    Brightness, Theme, ThemeCreatePayload, ThemeDiagnostic, ThemeGit, ThemePackage, ThemeTerminal,
-    ThemeTokens,
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
 };
 
-mod builder;
-mod color;
-mod theme;
+use snowfall_theme::{
+    builder::{Builder, ZedExporter},
+    color::Color,
+    colorblind::{self, Deficiency},
+    diff,
+    export::{self, ThemeExporter},
+    gallery, generate, harmony,
+    highlight::{self, SampleHighlighter},
+    import,
+    mockup::EditorMockup,
+    preview::Preview,
+    seasons,
+    show::TerminalPreview,
+    snowfall, spec,
+    template::TemplateExporter,
+    theme::{Theme, ThemePackage},
+    zed,
+};
 
 fn main() {
-    let dark_theme: Theme = Theme::new(ThemeCreatePayload {
-        name: String::from("Snowfall dark"),
-        brightness: Brightness::Dark,
-        accent: Color::from_hex("#84c4df"),
-        background: Color::from_hex("#24272d"),
-        foreground: Color::from_hex("#91a2a6"),
-        git: ThemeGit {
-            added: Color::from_hex("#BDB969"),
-            modified: Color::from_hex("#84c4df"),
-            removed: Color::from_hex("#FA7583"),
-        },
-        terminal: ThemeTerminal {
-            black: Color::from_hex("#1c1c1c"),
-            red: Color::from_hex("#cc6666"),
-            green: Color::from_hex("#bdb968"),
-            yellow: Color::from_hex("#f0c674"),
-            blue: Color::from_hex("#81a2be"),
-            magenta: Color::from_hex("#b193ba"),
-            cyan: Color::from_hex("#7fb2c8"),
-            white: Color::from_hex("#c8ccd4"),
-            bright_black: Color::from_hex("#636363"),
-            bright_red: Color::from_hex("#a04041"),
-            bright_green: Color::from_hex("#8b9440"),
-            bright_yellow: Color::from_hex("#EBD2A7"),
-            bright_blue: Color::from_hex("#5d7f9a"),
-            bright_magenta: Color::from_hex("#82658c"),
-            bright_cyan: Color::from_hex("#5e8d87"),
-            bright_white: Color::from_hex("#ffffff"),
-        },
-        tokens: ThemeTokens {
-            brackets: Color::from_hex("#7FB2C7"),
-            comment: Color::from_hex("#474c54"),
-            strings: Color::from_hex("#BDB969"),
-            types: Color::from_hex("#ACBDC3"),
-            functions: Color::from_hex("#7FB2C7"),
-            properties: Color::from_hex("#EBD2A7"),
-            keywords: Color::from_hex("#B08CBA"),
-            constants: Color::from_hex("#EBD2A7"),
-            operators: Color::from_hex("#B08CBA"),
-        },
-        diagnostics: ThemeDiagnostic {
-            error: Color::from_hex("#FA7583"),
-            warning: Color::from_hex("#EBD2A7"),
-            info: Color::from_hex("#9BCAFF"),
-        },
-    });
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let paths = args.get(1..).unwrap_or_default();
+    let package = snowfall();
+
+    match args.first().map(String::as_str) {
+        None => build(Builder::new(&package)),
+        Some("build") if paths.is_empty() => build(Builder::new(&package)),
+        Some("build") => {
+            let exporters = export::select(paths).unwrap_or_else(|error| fail(error));
+            build(Builder::with_exporters(&package, exporters));
+        }
+        Some("template") => {
+            if paths.is_empty() {
+                usage();
+            }
+            let exporters = paths
+                .iter()
+                .map(|path| {
+                    let exporter = TemplateExporter::new(Path::new(path), &package)
+                        .unwrap_or_else(|error| fail(error));
+                    Box::new(exporter) as Box<dyn ThemeExporter>
+                })
+                .collect();
+            build(Builder::with_exporters(&package, exporters));
+        }
+        Some("exporters") => {
+            for exporter in export::registry() {
+                println!("{:<10} .{}", exporter.name(), exporter.extension());
+            }
+        }
+        Some("render") => {
+            let highlighter = SampleHighlighter::new();
+            for theme in &themes(paths) {
+                written(Preview::new(theme).write(&root()));
+                written(EditorMockup::new(theme).write(&root()));
+                written(highlight::write(&root(), theme, &highlighter));
+            }
+            if paths.is_empty() {
+                for theme in main_flavors(&package) {
+                    written(Preview::new(theme).write_screenshot(&root()));
+                }
+            }
+        }
+        Some("banners") => {
+            for theme in main_flavors(&package) {
+                written(EditorMockup::new(theme).write_banner(&root()));
+            }
+        }
+        Some("colorblind") => {
+            for theme in themes(paths)
+                .iter()
+                .filter(|theme| !theme.is_background_syntax)
+            {
+                print!("{}", colorblind::report(theme));
+                for deficiency in Deficiency::ALL {
+                    let simulated = deficiency
+                        .simulate_theme(theme)
+                        .unwrap_or_else(|error| fail(error));
+                    written(Preview::new(&simulated).write(&root()));
+                }
+            }
+        }
+        Some("seasons") => {
+            let path = root()
+                .join("dist")
+                .join("zed")
+                .join("snowfall_seasons.json");
+            written(
+                fs::create_dir_all(path.parent().unwrap())
+                    .and_then(|_| fs::write(&path, ZedExporter::json(&seasons()))),
+            );
+        }
+        Some("harmony") => {
+            for theme in themes(paths)
+                .iter()
+                .filter(|theme| !theme.is_background_syntax)
+            {
+                print!("{}", harmony::report(theme));
+            }
+        }
+        Some("base16") => {
+            let exporters = export::select(&["base16".into(), "base24".into()]).unwrap();
+            build(Builder::with_exporters(&package, exporters));
+        }
+        Some("gallery") => written(gallery::write(&root(), &package)),
+        Some("import") => {
+            let Some(source) = paths.first() else {
+                usage();
+            };
+            let payload = import::import(Path::new(source)).unwrap_or_else(|error| fail(error));
+            match paths.get(1) {
+                Some(target) => written(spec::write(Path::new(target), &payload)),
+                None => print!(
+                    "{}",
+                    spec::to_string(&payload).unwrap_or_else(|error| fail(error))
+                ),
+            }
+        }
+        Some("generate") => {
+            let [background, foreground, accent, hues @ ..] = paths else {
+                usage();
+            };
+            let color = |hex: &String| {
+                Color::parse(hex).unwrap_or_else(|| fail(format!("Invalid color: {}", hex)))
+            };
+            let mut seeds = generate::Seeds::new(
+                "Generated".into(),
+                color(background),
+                color(foreground),
+                color(accent),
+            );
+            if !hues.is_empty() {
+                let hues = hues
+                    .iter()
+                    .map(|hue| {
+                        hue.parse::<f32>()
+                            .unwrap_or_else(|_| fail(format!("Invalid hue: {}", hue)))
+                    })
+                    .collect::<Vec<_>>();
+                seeds.hues = hues.try_into().unwrap_or_else(|_| {
+                    fail("Expected six hues: red, yellow, green, cyan, blue, magenta".into())
+                });
+            }
+            let generated = generate::generate(&seeds);
+            for warning in generated.warnings {
+                eprintln!("warning: {}", warning);
+            }
+            print!(
+                "{}",
+                spec::to_string(&generated.payload).unwrap_or_else(|error| fail(error))
+            );
+        }
+        Some("diff") => {
+            let [old, new] = paths else {
+                usage();
+            };
+            let read = |path: &String| {
+                fs::read_to_string(path)
+                    .unwrap_or_else(|error| fail(format!("Error reading {}: {}", path, error)))
+            };
+            print!(
+                "{}",
+                diff::report(&read(old), &read(new)).unwrap_or_else(|error| fail(error))
+            );
+        }
+        Some("show") => {
+            let [name] = paths else {
+                usage();
+            };
+            print!("{}", TerminalPreview::new(&theme(name)).render());
+        }
+        Some("parse") => {
+            let Some(source) = paths.first() else {
+                usage();
+            };
+            let json = fs::read_to_string(source)
+                .unwrap_or_else(|error| fail(format!("Error reading {}: {}", source, error)));
+            let parsed = zed::parse(&json).unwrap_or_else(|error| fail(error));
+
+            for zed::ParsedTheme { theme, warnings } in parsed {
+                for warning in warnings {
+                    eprintln!("{}: {}", theme.name, warning);
+                }
+                match paths.get(1) {
+                    Some(dir) => written(spec::write(
+                        &Path::new(dir).join(format!("{}.toml", theme.slug())),
+                        &theme.payload(),
+                    )),
+                    None => println!(
+                        "{}",
+                        spec::to_string(&theme.payload()).unwrap_or_else(|error| fail(error))
+                    ),
+                }
+            }
+        }
+        Some(command) => {
+            eprintln!("Unknown command: {}", command);
+            usage();
+        }
+    }
+}
+
+fn usage() -> ! {
+    eprintln!("Usage: snowfall-zed [build [<exporter>...]]");
+    eprintln!("       snowfall-zed exporters");
+    eprintln!("       snowfall-zed template <name.ext.tera>...");
+    eprintln!("       snowfall-zed render [<spec.toml>...]");
+    eprintln!("       snowfall-zed banners");
+    eprintln!("       snowfall-zed seasons");
+    eprintln!("       snowfall-zed colorblind [<spec.toml>...]");
+    eprintln!("       snowfall-zed harmony [<spec.toml>...]");
+    eprintln!("       snowfall-zed base16");
+    eprintln!("       snowfall-zed gallery");
+    eprintln!("       snowfall-zed import <theme.json|.tmTheme|.yaml|.tokens.json> [<spec.toml>]");
+    eprintln!("       snowfall-zed generate <background> <foreground> <accent> [<hue>...]");
+    eprintln!("       snowfall-zed parse <zed-theme.json> [<spec dir>]");
+    eprintln!("       snowfall-zed diff <old.json> <new.json>");
+    eprintln!("       snowfall-zed show <theme name|spec.toml>");
+    std::process::exit(1);
+}
+
+/// The repository root: the CLI runs from `builder/` and writes its output
+/// next to it.
+fn root() -> PathBuf {
+    env::current_dir()
+        .unwrap_or_else(|error| fail(error.to_string()))
+        .join("..")
+}
+
+/// Runs a builder, writing into the repository root.
+fn build(builder: Builder) {
+    written(builder.build(&root()));
+}
+
+/// Exits with the error when writing output failed.
+fn written(result: io::Result<()>) {
+    result.unwrap_or_else(|error| fail(format!("Error writing output: {}", error)));
+}
+
+fn fail(error: String) -> ! {
+    eprintln!("{}", error);
+    std::process::exit(1);
+}
+
+/// The first theme of each appearance, the main flavors shown in the README.
+fn main_flavors(package: &ThemePackage) -> Vec<&Theme> {
+    let mut seen = vec![];
+    package
+        .themes
+        .iter()
+        .filter(|theme| {
+            let first = !seen.contains(&theme.brightness);
+            seen.push(theme.brightness.clone());
+            first
+        })
+        .collect()
+}
+
+/// Themes from the given palette spec files, or the Snowfall themes when
+/// there are none.
+fn themes(paths: &[String]) -> Vec<Theme> {
+    if paths.is_empty() {
+        return snowfall().themes;
+    }
+
+    paths
+        .iter()
+        .map(|path| {
+            spec::read(Path::new(path))
+                .and_then(Theme::new)
+                .unwrap_or_else(|error| fail(error))
+        })
+        .collect()
+}
 
-    let light_theme = Theme::new(ThemeCreatePayload {
-        name: String::from("Snowfall light"),
-        brightness: Brightness::Light,
-        background: Color::from_hex("#ffffff"),
-        foreground: Color::from_hex("#5C6165"),
-        accent: Color::from_hex("#88C0D0"),
-        tokens: ThemeTokens {
-            brackets: Color::from_hex("#50afce"),
-            comment: Color::from_hex("#bdc1c8"),    // base03
-            strings: Color::from_hex("#85B300"),    // base0B
-            constants: Color::from_hex("#d59225"),  // base09
-            keywords: Color::from_hex("#a25cb5"),   // base0E
-            operators: Color::from_hex("#ACBDC3"),  // base05,
-            properties: Color::from_hex("#d59225"), // base0D
-            types: Color::from_hex("#5C6165"),      // base0C
-            functions: Color::from_hex("#50afce"),
-        },
-        git: ThemeGit {
-            added: Color::from_hex("#8ACB6B"),
-            modified: Color::from_hex("#88C0D0"),
-            removed: Color::from_hex("#FF8F9B"),
-        },
-        diagnostics: ThemeDiagnostic {
-            error: Color::from_hex("#FA7583"),
-            warning: Color::from_hex("#ec9c62"),
-            info: Color::from_hex("#9BCAFF"),
-        },
-        terminal: ThemeTerminal {
-            black: Color::from_hex("#1c1c1c"),
-            red: Color::from_hex("#cc6666"),
-            green: Color::from_hex("#bdb968"),
-            yellow: Color::from_hex("#f0c674"),
-            blue: Color::from_hex("#81a2be"),
-            magenta: Color::from_hex("#b193ba"),
-            cyan: Color::from_hex("#7fb2c8"),
-            white: Color::from_hex("#c8ccd4"),
-            bright_black: Color::from_hex("#636363"),
-            bright_red: Color::from_hex("#a04041"),
-            bright_green: Color::from_hex("#8b9440"),
-            bright_yellow: Color::from_hex("#ec9c62"),
-            bright_blue: Color::from_hex("#5d7f9a"),
-            bright_magenta: Color::from_hex("#82658c"),
-            bright_cyan: Color::from_hex("#5e8d87"),
-            bright_white: Color::from_hex("#ffffff"),
-        },
-    });
+/// A Snowfall theme by name or slug (`snowfall_dark`), or a palette spec file.
+fn theme(name: &str) -> Theme {
+    if Path::new(name).is_file() {
+        return themes(&[name.to_string()]).remove(0);
+    }
 
-    Builder::new(&ThemePackage {
-        name: "Snowfall".into(),
-        author: "freethinkel".into(),
-        themes: vec![
-            dark_theme.clone(),
-            dark_theme.clone().to_bg_syntax(),
-            light_theme.clone(),
-            light_theme.clone().to_bg_syntax(),
-        ],
-    })
-    .build();
+    let themes = snowfall().themes;
+    let slugs = themes.iter().map(Theme::slug).collect::<Vec<_>>();
+    themes
+        .into_iter()
+        .find(|theme| theme.name.eq_ignore_ascii_case(name) || theme.slug() == name)
+        .unwrap_or_else(|| {
+            fail(format!(
+                "Unknown theme: {} (expected a spec file or one of {})",
+                name,
+                slugs.join(", ")
+            ))
+        })
 }
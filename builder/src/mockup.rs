@@ -0,0 +1,358 @@
+use std::{fmt::Write, fs, io, path::Path};
+
+use serde_json::Value;
+
+use crate::{
+    color::Color,
+    export::escape,
+    preview::{
+        fill, highlighted_code, rasterize, CODE_CHAR_WIDTH, CODE_FONT_SIZE, CODE_LINE_HEIGHT,
+        FONT_FAMILY,
+    },
+    sample,
+    theme::{Brightness, Theme},
+};
+
+const WIDTH: f32 = 1280.0;
+const HEIGHT: f32 = 800.0;
+const TITLE_BAR: f32 = 36.0;
+const TAB_BAR: f32 = 34.0;
+const STATUS_BAR: f32 = 28.0;
+const PANEL: f32 = 240.0;
+const GUTTER: f32 = 64.0;
+const TERMINAL: f32 = 200.0;
+const UI_FONT_SIZE: f32 = 13.0;
+
+/// Draws a mock Zed window from a theme's serialized `style`, so every region
+/// uses the same key Zed reads (`title_bar.background`, `tab.active_background`,
+/// `editor.line_number`, ...).
+pub struct EditorMockup<'a> {
+    theme: &'a Theme,
+    style: Value,
+}
+
+impl<'a> EditorMockup<'a> {
+    pub fn new(theme: &'a Theme) -> Self {
+        let value = serde_json::to_value(theme).unwrap();
+
+        Self {
+            theme,
+            style: value["style"].clone(),
+        }
+    }
+
+    /// Color of a style key, e.g. `tab.active_background`.
+    fn color(&self, key: &str) -> Color {
+        let hex = self.style[key]
+            .as_str()
+            .unwrap_or_else(|| panic!("Missing style key {}", key));
+        Color::from_hex(hex)
+    }
+
+    fn player(&self, key: &str) -> Color {
+        Color::from_hex(self.style["players"][0][key].as_str().unwrap())
+    }
+
+    pub fn svg(&self) -> String {
+        let mut body = String::new();
+        let content_top = TITLE_BAR;
+        let content_bottom = HEIGHT - STATUS_BAR;
+        let editor_left = PANEL + 1.0;
+        let terminal_top = content_bottom - TERMINAL;
+
+        self.title_bar(&mut body);
+        self.panel(&mut body, content_top, content_bottom);
+        self.tab_bar(&mut body, editor_left, content_top);
+        self.editor(&mut body, editor_left, content_top + TAB_BAR, terminal_top);
+        self.terminal(&mut body, editor_left, terminal_top, content_bottom);
+        self.status_bar(&mut body, content_bottom);
+
+        format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{WIDTH}" height="{HEIGHT}" viewBox="0 0 {WIDTH} {HEIGHT}" font-family="{FONT_FAMILY}"><rect width="100%" height="100%" {}/>{body}</svg>"#,
+            fill(self.color("background")),
+        )
+    }
+
+    pub fn png(&self) -> Vec<u8> {
+        rasterize(&self.svg())
+    }
+
+    /// Writes `assets/previews/<theme>_editor.svg` and `.png` under `root`.
+    pub fn write(&self, root: &Path) -> io::Result<()> {
+        let dir = root.join("assets").join("previews");
+        fs::create_dir_all(&dir)?;
+
+        let slug = self.theme.slug();
+        fs::write(dir.join(format!("{}_editor.svg", slug)), self.svg())?;
+        fs::write(dir.join(format!("{}_editor.png", slug)), self.png())
+    }
+
+    /// Overwrites `assets/banner_<appearance>.png` under `root`, the banners
+    /// shown in the README.
+    pub fn write_banner(&self, root: &Path) -> io::Result<()> {
+        let appearance = match self.theme.brightness {
+            Brightness::Dark => "dark",
+            Brightness::Light => "light",
+        };
+        let dir = root.join("assets");
+        fs::create_dir_all(&dir)?;
+        fs::write(dir.join(format!("banner_{}.png", appearance)), self.png())
+    }
+
+    fn rect(&self, body: &mut String, x: f32, y: f32, width: f32, height: f32, key: &str) {
+        let _ = write!(
+            body,
+            r#"<rect x="{x}" y="{y}" width="{width}" height="{height}" {}/>"#,
+            fill(self.color(key))
+        );
+    }
+
+    fn text(&self, body: &mut String, x: f32, y: f32, key: &str, text: &str) {
+        let _ = write!(
+            body,
+            r#"<text x="{x}" y="{y}" font-size="{UI_FONT_SIZE}" {} xml:space="preserve">{}</text>"#,
+            fill(self.color(key)),
+            escape(text)
+        );
+    }
+
+    fn title_bar(&self, body: &mut String) {
+        self.rect(body, 0.0, 0.0, WIDTH, TITLE_BAR, "title_bar.background");
+        self.rect(body, 0.0, TITLE_BAR - 1.0, WIDTH, 1.0, "border");
+
+        for (index, key) in ["error", "warning", "success"].iter().enumerate() {
+            let _ = write!(
+                body,
+                r#"<circle cx="{}" cy="{}" r="6" {}/>"#,
+                20.0 + index as f32 * 20.0,
+                TITLE_BAR / 2.0,
+                fill(self.color(key))
+            );
+        }
+        self.text(body, 96.0, 23.0, "text", "snowfall");
+        self.text(body, 170.0, 23.0, "text.muted", "main");
+        self.text(body, WIDTH - 200.0, 23.0, "text.muted", &self.theme.name);
+    }
+
+    fn panel(&self, body: &mut String, top: f32, bottom: f32) {
+        self.rect(body, 0.0, top, PANEL, bottom - top, "panel.background");
+        self.rect(body, PANEL, top, 1.0, bottom - top, "border");
+
+        let entries = [
+            ("v snowfall", "text", None),
+            ("  v builder", "text", None),
+            ("    v src", "text", None),
+            ("      builder.rs", "text", Some("version_control.modified")),
+            ("      color.rs", "text", None),
+            ("      main.rs", "text", Some("version_control.modified")),
+            ("      theme.rs", "text", Some("version_control.added")),
+            ("    Cargo.toml", "text", None),
+            ("  > target", "version_control.ignored", None),
+            ("  > themes", "text", None),
+            ("  README.md", "text", None),
+        ];
+        for (index, (name, key, git)) in entries.iter().enumerate() {
+            let y = top + 8.0 + index as f32 * 26.0;
+            if index == 5 {
+                self.rect(body, 0.0, y, PANEL, 26.0, "element.selected");
+                self.rect(body, 0.0, y, 2.0, 26.0, "panel.focused_border");
+            }
+            self.text(body, 12.0, y + 18.0, git.unwrap_or(key), name);
+        }
+    }
+
+    fn tab_bar(&self, body: &mut String, left: f32, top: f32) {
+        self.rect(body, left, top, WIDTH - left, TAB_BAR, "tab_bar.background");
+        self.rect(body, left, top + TAB_BAR - 1.0, WIDTH - left, 1.0, "border");
+
+        let tabs = ["main.rs", "theme.rs", "color.rs"];
+        let mut x = left;
+        for (index, name) in tabs.iter().enumerate() {
+            let width = 40.0 + name.len() as f32 * 8.0;
+            let active = index == 0;
+            let (background, text) = match active {
+                true => ("tab.active_background", "text"),
+                false => ("tab.inactive_background", "text.muted"),
+            };
+            self.rect(body, x, top, width, TAB_BAR, background);
+            if active {
+                // The active tab merges into the editor below it.
+                self.rect(body, x, top + TAB_BAR - 1.0, width, 1.0, background);
+            }
+            self.rect(body, x + width, top, 1.0, TAB_BAR, "border");
+            self.text(body, x + 20.0, top + 22.0, text, name);
+            x += width + 1.0;
+        }
+    }
+
+    fn editor(&self, body: &mut String, left: f32, top: f32, bottom: f32) {
+        let code_left = left + GUTTER;
+        let lines = sample::RUST;
+        let active_line = 8;
+        let first_line = top + 8.0;
+
+        self.rect(
+            body,
+            left,
+            top,
+            WIDTH - left,
+            bottom - top,
+            "editor.background",
+        );
+        self.rect(
+            body,
+            left,
+            top,
+            GUTTER,
+            bottom - top,
+            "editor.gutter.background",
+        );
+
+        for row in 0..lines.len() {
+            let y = first_line + row as f32 * CODE_LINE_HEIGHT;
+            let (key, number) = match row == active_line {
+                true => ("editor.active_line_number", row + 1),
+                false => ("editor.line_number", row + 1),
+            };
+            let _ = write!(
+                body,
+                r#"<text x="{}" y="{}" font-size="{CODE_FONT_SIZE}" text-anchor="end" {}>{number}</text>"#,
+                left + GUTTER - 20.0,
+                y + CODE_LINE_HEIGHT - 5.0,
+                fill(self.color(key)),
+            );
+
+            let git = match row {
+                2..=5 => Some("version_control.added"),
+                8 => Some("version_control.modified"),
+                _ => None,
+            };
+            if let Some(git) = git {
+                self.rect(body, left + GUTTER - 8.0, y, 3.0, CODE_LINE_HEIGHT, git);
+            }
+        }
+
+        // Selection on `celsius` followed by the local player's cursor.
+        let selection_y = first_line + active_line as f32 * CODE_LINE_HEIGHT;
+        let selection_x = code_left + 8.0 + 23.0 * CODE_CHAR_WIDTH;
+        let _ = write!(
+            body,
+            r#"<rect x="{selection_x}" y="{selection_y}" width="{}" height="{CODE_LINE_HEIGHT}" {}/><rect x="{}" y="{selection_y}" width="2" height="{CODE_LINE_HEIGHT}" {}/>"#,
+            7.0 * CODE_CHAR_WIDTH,
+            fill(self.player("selection")),
+            selection_x + 7.0 * CODE_CHAR_WIDTH,
+            fill(self.player("cursor")),
+        );
+
+        highlighted_code(body, self.theme, lines, code_left + 8.0, first_line);
+    }
+
+    fn terminal(&self, body: &mut String, left: f32, top: f32, bottom: f32) {
+        self.rect(
+            body,
+            left,
+            top,
+            WIDTH - left,
+            bottom - top,
+            "terminal.background",
+        );
+        self.rect(body, left, top, WIDTH - left, 1.0, "border");
+
+        let lines: &[&[(&str, &str)]] = &[
+            &[
+                ("terminal.ansi.green", "~/snowfall "),
+                ("terminal.ansi.blue", "builder "),
+                ("terminal.ansi.magenta", "(main) "),
+                ("terminal.foreground", "$ cargo run"),
+            ],
+            &[
+                ("terminal.ansi.bright_green", "   Compiling "),
+                ("terminal.foreground", "snowfall-zed v0.2.1"),
+            ],
+            &[
+                ("terminal.ansi.yellow", "warning"),
+                ("terminal.foreground", ": unused variable: `flake`"),
+            ],
+            &[
+                ("terminal.ansi.bright_green", "    Finished "),
+                ("terminal.foreground", "`dev` profile in 1.04s"),
+            ],
+            &[
+                ("terminal.ansi.bright_green", "     Running "),
+                ("terminal.ansi.cyan", "`target/debug/snowfall-zed`"),
+            ],
+            &[
+                ("terminal.ansi.red", "error"),
+                ("terminal.foreground", ": nothing to melt"),
+            ],
+        ];
+
+        for (row, line) in lines.iter().enumerate() {
+            let mut x = left + 16.0;
+            let y = top + 12.0 + (row as f32 + 1.0) * CODE_LINE_HEIGHT - 5.0;
+            for (key, text) in line.iter() {
+                let _ = write!(
+                    body,
+                    r#"<text x="{x}" y="{y}" font-size="{CODE_FONT_SIZE}" {} xml:space="preserve">{}</text>"#,
+                    fill(self.color(key)),
+                    escape(text)
+                );
+                x += text.chars().count() as f32 * CODE_CHAR_WIDTH;
+            }
+        }
+    }
+
+    fn status_bar(&self, body: &mut String, top: f32) {
+        self.rect(body, 0.0, top, WIDTH, STATUS_BAR, "status_bar.background");
+        self.rect(body, 0.0, top, WIDTH, 1.0, "border");
+
+        self.text(body, 12.0, top + 19.0, "error", "x 1");
+        self.text(body, 52.0, top + 19.0, "warning", "! 2");
+        self.text(body, 92.0, top + 19.0, "info", "i 3");
+        self.text(body, WIDTH - 220.0, top + 19.0, "text.muted", "9:35");
+        self.text(body, WIDTH - 160.0, top + 19.0, "text.accent", "Rust");
+        self.text(body, WIDTH - 110.0, top + 19.0, "icon.muted", "UTF-8");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uses_serialized_style_keys() {
+        let theme = &crate::snowfall().themes[0];
+        let mockup = EditorMockup::new(theme);
+        let svg = mockup.svg();
+
+        for key in [
+            "title_bar.background",
+            "tab.active_background",
+            "tab.inactive_background",
+            "panel.background",
+            "status_bar.background",
+        ] {
+            assert!(svg.contains(&mockup.color(key).to_hex()), "{}", key);
+        }
+        assert_eq!(&mockup.png()[1..4], b"PNG");
+    }
+
+    #[test]
+    fn draws_each_region_from_its_own_key() {
+        let mut theme = crate::snowfall().themes[0].clone();
+        theme.tokens.keywords = Color::from_hex("#030303");
+        let mut mockup = EditorMockup::new(&theme);
+        mockup.style["editor.background"] = "#010101".into();
+        mockup.style["status_bar.background"] = "#020202".into();
+        let svg = mockup.svg();
+
+        assert_eq!(mockup.style["syntax"]["keyword"]["color"], "#030303");
+        assert!(
+            svg.contains(r##"<rect x="241" y="70" width="1039" height="502" fill="#010101"/>"##)
+        );
+        assert!(svg.contains(r##"<rect x="0" y="772" width="1280" height="28" fill="#020202"/>"##));
+        assert!(svg.contains(r##"fill="#030303">pub struct </tspan>"##));
+        assert_eq!(svg.matches("#010101").count(), 1);
+        assert_eq!(svg.matches("#020202").count(), 1);
+    }
+}
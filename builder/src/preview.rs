@@ -0,0 +1,250 @@
+use std::{fmt::Write, fs, io, path::Path};
+
+use resvg::{tiny_skia, usvg};
+
+use crate::{
+    builder::SyntaxTheme,
+    color::Color,
+    export::escape,
+    sample::{self, SampleLine},
+    theme::{Brightness, Theme},
+};
+
+const PADDING: f32 = 32.0;
+const COLUMNS: usize = 8;
+const SWATCH_WIDTH: f32 = 104.0;
+const SWATCH_HEIGHT: f32 = 48.0;
+const SWATCH_GAP: f32 = 12.0;
+const SWATCH_ROW: f32 = SWATCH_HEIGHT + 40.0;
+const SECTION_TITLE: f32 = 28.0;
+pub const CODE_FONT_SIZE: f32 = 14.0;
+pub const CODE_LINE_HEIGHT: f32 = 20.0;
+// Advance of DejaVu Sans Mono at 14px, the fallback monospace font.
+pub const CODE_CHAR_WIDTH: f32 = 8.43;
+pub const FONT_FAMILY: &str = "JetBrains Mono, DejaVu Sans Mono, monospace";
+
+/// Renders a palette swatch grid and a highlighted code sample for a theme,
+/// as SVG or as a PNG rasterized on the CPU.
+pub struct Preview<'a> {
+    theme: &'a Theme,
+}
+
+impl<'a> Preview<'a> {
+    pub fn new(theme: &'a Theme) -> Self {
+        Self { theme }
+    }
+
+    pub fn svg(&self) -> String {
+        let theme = self.theme;
+        let width = PADDING * 2.0 + COLUMNS as f32 * (SWATCH_WIDTH + SWATCH_GAP) - SWATCH_GAP;
+        let mut body = String::new();
+        let mut y = PADDING;
+
+        let _ = write!(
+            body,
+            r#"<text x="{PADDING}" y="{}" font-size="20" font-weight="bold" fill="{}">{}</text>"#,
+            y + 20.0,
+            theme.foreground.to_hex(),
+            escape(&theme.name)
+        );
+        y += 48.0;
+
+        let sections = [
+            ("Tokens", theme.tokens.colors()),
+            ("Terminal", theme.terminal.colors()),
+            ("Git", theme.git.colors()),
+            ("Diagnostics", theme.diagnostics.colors()),
+        ];
+        for (title, colors) in sections {
+            y = self.swatches(&mut body, y, title, &colors);
+        }
+
+        y = self.code(&mut body, y, width, sample::RUST);
+
+        format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}" font-family="{FONT_FAMILY}"><rect width="100%" height="100%" {background}/>{body}</svg>"#,
+            height = y + PADDING,
+            background = fill(theme.background),
+        )
+    }
+
+    pub fn png(&self) -> Vec<u8> {
+        rasterize(&self.svg())
+    }
+
+    /// Writes `assets/previews/<theme>.svg` and `.png` under `root`.
+    pub fn write(&self, root: &Path) -> io::Result<()> {
+        let dir = root.join("assets").join("previews");
+        fs::create_dir_all(&dir)?;
+
+        let slug = self.theme.slug();
+        fs::write(dir.join(format!("{}.svg", slug)), self.svg())?;
+        fs::write(dir.join(format!("{}.png", slug)), self.png())
+    }
+
+    /// Overwrites `assets/<appearance>_theme.png` under `root`, the
+    /// screenshots shown in the README.
+    pub fn write_screenshot(&self, root: &Path) -> io::Result<()> {
+        let appearance = match self.theme.brightness {
+            Brightness::Dark => "dark",
+            Brightness::Light => "light",
+        };
+        let dir = root.join("assets");
+        fs::create_dir_all(&dir)?;
+        fs::write(dir.join(format!("{}_theme.png", appearance)), self.png())
+    }
+
+    fn swatches(&self, body: &mut String, y: f32, title: &str, colors: &[(&str, Color)]) -> f32 {
+        let theme = self.theme;
+        let _ = write!(
+            body,
+            r#"<text x="{PADDING}" y="{}" font-size="13" font-weight="bold" {}>{}</text>"#,
+            y + 14.0,
+            fill(theme.foreground.with_opacity(0.8)),
+            title
+        );
+        let y = y + SECTION_TITLE;
+
+        for (index, (name, color)) in colors.iter().enumerate() {
+            let x = PADDING + (index % COLUMNS) as f32 * (SWATCH_WIDTH + SWATCH_GAP);
+            let y = y + (index / COLUMNS) as f32 * SWATCH_ROW;
+            let _ = write!(
+                body,
+                r#"<rect x="{x}" y="{y}" width="{SWATCH_WIDTH}" height="{SWATCH_HEIGHT}" rx="6" {} stroke="{}"/>"#,
+                fill(*color),
+                theme.border().to_hex(),
+            );
+            let _ = write!(
+                body,
+                r#"<text x="{x}" y="{}" font-size="11" {}>{}</text><text x="{x}" y="{}" font-size="11" {}>{}</text>"#,
+                y + SWATCH_HEIGHT + 16.0,
+                fill(theme.foreground),
+                name,
+                y + SWATCH_HEIGHT + 30.0,
+                fill(theme.foreground.with_opacity(0.6)),
+                color.to_hex(),
+            );
+        }
+
+        y + colors.len().div_ceil(COLUMNS) as f32 * SWATCH_ROW + 8.0
+    }
+
+    fn code(&self, body: &mut String, y: f32, width: f32, lines: &[SampleLine]) -> f32 {
+        let theme = self.theme;
+        let height = lines.len() as f32 * CODE_LINE_HEIGHT + 24.0;
+        let _ = write!(
+            body,
+            r#"<rect x="{PADDING}" y="{y}" width="{}" height="{height}" rx="8" {} stroke="{}"/>"#,
+            width - PADDING * 2.0,
+            fill(theme.background),
+            theme.border().to_hex(),
+        );
+
+        highlighted_code(body, theme, lines, PADDING + 16.0, y + 12.0);
+
+        y + height
+    }
+}
+
+/// Writes pre-highlighted sample lines as SVG text, starting with the top of
+/// the first line at `(x, y)`. Lines are `CODE_LINE_HEIGHT` apart.
+pub fn highlighted_code(body: &mut String, theme: &Theme, lines: &[SampleLine], x: f32, y: f32) {
+    for (row, line) in lines.iter().enumerate() {
+        let baseline = y + (row as f32 + 1.0) * CODE_LINE_HEIGHT - 5.0;
+        let mut column = 0;
+        let mut spans = String::new();
+
+        for (capture, text) in line.iter() {
+            let x = x + column as f32 * CODE_CHAR_WIDTH;
+            let length = text.chars().count();
+            let style = match *capture {
+                "" => None,
+                capture => Some(theme.highlight(capture)),
+            };
+
+            if let Some(background) = style.as_ref().and_then(|style| style.background) {
+                let _ = write!(
+                    body,
+                    r#"<rect x="{x}" y="{}" width="{}" height="{}" rx="2" {}/>"#,
+                    baseline - CODE_FONT_SIZE,
+                    length as f32 * CODE_CHAR_WIDTH,
+                    CODE_LINE_HEIGHT - 2.0,
+                    fill(background),
+                );
+            }
+
+            let color = style.as_ref().map_or(theme.foreground, |style| style.color);
+            let italic = style.as_ref().is_some_and(|style| style.italic);
+            let bold = style.as_ref().is_some_and(|style| style.bold);
+            let _ = write!(
+                spans,
+                r#"<tspan x="{x}" {}{}{}>{}</tspan>"#,
+                fill(color),
+                if italic {
+                    r#" font-style="italic""#
+                } else {
+                    ""
+                },
+                if bold { r#" font-weight="bold""# } else { "" },
+                escape(text),
+            );
+            column += length;
+        }
+
+        let _ = write!(
+            body,
+            r#"<text y="{baseline}" font-size="{CODE_FONT_SIZE}" xml:space="preserve">{spans}</text>"#,
+        );
+    }
+}
+
+/// `fill` attributes for a color, splitting the alpha channel into `fill-opacity`.
+pub fn fill(color: Color) -> String {
+    match color.alpha() {
+        1.0 => format!(r#"fill="{}""#, color.to_hex()),
+        alpha => format!(
+            r#"fill="{}" fill-opacity="{:.3}""#,
+            color.with_opacity(1.0).to_hex(),
+            alpha
+        ),
+    }
+}
+
+/// Rasterizes an SVG document to PNG bytes, using whatever system fonts are
+/// installed. Runs entirely on the CPU.
+pub fn rasterize(svg: &str) -> Vec<u8> {
+    let mut options = usvg::Options::default();
+    options.fontdb_mut().load_system_fonts();
+
+    let tree = usvg::Tree::from_str(svg, &options).expect("Error parsing SVG");
+    let size = tree.size().to_int_size();
+    let mut pixmap =
+        tiny_skia::Pixmap::new(size.width(), size.height()).expect("Error allocating pixmap");
+    resvg::render(&tree, tiny_skia::Transform::default(), &mut pixmap.as_mut());
+
+    pixmap.encode_png().expect("Error encoding PNG")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_every_theme() {
+        for theme in crate::snowfall().themes {
+            let png = Preview::new(&theme).png();
+            assert_eq!(&png[1..4], b"PNG");
+        }
+    }
+
+    #[test]
+    fn splits_alpha_into_fill_opacity() {
+        let accent = Color::from_hex("#84c4df");
+
+        assert_eq!(fill(accent), r##"fill="#84c4df""##);
+        assert_eq!(
+            fill(accent.with_opacity(0.2)),
+            r##"fill="#84c4df" fill-opacity="0.200""##
+        );
+    }
+}
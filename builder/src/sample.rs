@@ -0,0 +1,176 @@
+/// A line of pre-highlighted code: `(capture, text)` spans, where an empty
+/// capture means plain foreground text.
+pub type SampleLine = &'static [(&'static str, &'static str)];
+
+pub const RUST: &[SampleLine] = &[
+    &[("comment.doc", "/// A single flake of the first snow.")],
+    &[
+        ("attribute", "#"),
+        ("punctuation.bracket", "["),
+        ("attribute", "derive"),
+        ("punctuation.bracket", "("),
+        ("type", "Debug"),
+        ("punctuation.delimiter", ", "),
+        ("type", "Clone"),
+        ("punctuation.bracket", ")]"),
+    ],
+    &[
+        ("keyword", "pub struct "),
+        ("type", "Snowflake"),
+        ("", " "),
+        ("punctuation.bracket", "{"),
+    ],
+    &[
+        ("", "    "),
+        ("property", "arms"),
+        ("punctuation.delimiter", ": "),
+        ("type", "u8"),
+        ("punctuation.delimiter", ","),
+    ],
+    &[
+        ("", "    "),
+        ("property", "name"),
+        ("punctuation.delimiter", ": "),
+        ("operator", "&"),
+        ("keyword", "'static "),
+        ("type", "str"),
+        ("punctuation.delimiter", ","),
+    ],
+    &[("punctuation.bracket", "}")],
+    &[],
+    &[
+        ("keyword", "impl "),
+        ("type", "Snowflake"),
+        ("", " "),
+        ("punctuation.bracket", "{"),
+    ],
+    &[
+        ("", "    "),
+        ("keyword", "pub fn "),
+        ("function", "melt"),
+        ("punctuation.bracket", "("),
+        ("operator", "&"),
+        ("variable.special", "self"),
+        ("punctuation.delimiter", ", "),
+        ("variable", "celsius"),
+        ("punctuation.delimiter", ": "),
+        ("type", "f32"),
+        ("punctuation.bracket", ")"),
+        ("operator", " -> "),
+        ("type", "Option"),
+        ("punctuation.bracket", "<"),
+        ("type", "String"),
+        ("punctuation.bracket", ">"),
+        ("", " "),
+        ("punctuation.bracket", "{"),
+    ],
+    &[
+        ("", "        "),
+        ("comment", "// Nothing happens below zero"),
+    ],
+    &[
+        ("", "        "),
+        ("keyword", "if "),
+        ("variable", "celsius"),
+        ("operator", " < "),
+        ("number", "0.0"),
+        ("", " "),
+        ("punctuation.bracket", "{"),
+    ],
+    &[
+        ("", "            "),
+        ("keyword", "return "),
+        ("type", "None"),
+        ("punctuation.delimiter", ";"),
+    ],
+    &[("", "        "), ("punctuation.bracket", "}")],
+    &[
+        ("", "        "),
+        ("type", "Some"),
+        ("punctuation.bracket", "("),
+        ("function", "format!"),
+        ("punctuation.bracket", "("),
+        ("string", "\"{} melted"),
+        ("string.escape", "\\n"),
+        ("string", "\""),
+        ("punctuation.delimiter", ", "),
+        ("variable.special", "self"),
+        ("punctuation.delimiter", "."),
+        ("property", "name"),
+        ("punctuation.bracket", "))"),
+    ],
+    &[("", "    "), ("punctuation.bracket", "}")],
+    &[("punctuation.bracket", "}")],
+];
+
+pub const TYPESCRIPT: &[SampleLine] = &[
+    &[
+        ("keyword", "import "),
+        ("punctuation.bracket", "{ "),
+        ("type", "Flake"),
+        ("punctuation.bracket", " }"),
+        ("keyword", " from "),
+        ("string", "\"./flake\""),
+        ("punctuation.delimiter", ";"),
+    ],
+    &[],
+    &[("comment", "// Flakes settle in the order they fall")],
+    &[
+        ("keyword", "export function "),
+        ("function", "settle"),
+        ("punctuation.bracket", "("),
+        ("variable", "flakes"),
+        ("punctuation.delimiter", ": "),
+        ("type", "Flake"),
+        ("punctuation.bracket", "[]"),
+        ("punctuation.delimiter", ", "),
+        ("variable", "depth"),
+        ("operator", " = "),
+        ("number", "12"),
+        ("punctuation.bracket", ")"),
+        ("", " "),
+        ("punctuation.bracket", "{"),
+    ],
+    &[
+        ("", "  "),
+        ("keyword", "return "),
+        ("variable", "flakes"),
+        ("punctuation.delimiter", "."),
+        ("function", "map"),
+        ("punctuation.bracket", "(("),
+        ("variable", "flake"),
+        ("punctuation.delimiter", ", "),
+        ("variable", "index"),
+        ("punctuation.bracket", ")"),
+        ("operator", " => "),
+        ("punctuation.bracket", "({"),
+    ],
+    &[
+        ("", "    "),
+        ("operator", "..."),
+        ("variable", "flake"),
+        ("punctuation.delimiter", ","),
+    ],
+    &[
+        ("", "    "),
+        ("property", "layer"),
+        ("punctuation.delimiter", ": "),
+        ("variable", "index"),
+        ("operator", " % "),
+        ("variable", "depth"),
+        ("punctuation.delimiter", ","),
+    ],
+    &[
+        ("", "    "),
+        ("property", "melted"),
+        ("punctuation.delimiter", ": "),
+        ("boolean", "false"),
+        ("punctuation.delimiter", ","),
+    ],
+    &[
+        ("", "  "),
+        ("punctuation.bracket", "}))"),
+        ("punctuation.delimiter", ";"),
+    ],
+    &[("punctuation.bracket", "}")],
+];
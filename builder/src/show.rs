@@ -0,0 +1,221 @@
+use std::fmt::Write;
+
+use serde_json::Value;
+
+use crate::{
+    builder::SyntaxTheme,
+    color::Color,
+    sample::{self, SampleLine},
+    theme::Theme,
+};
+
+const WIDTH: usize = 72;
+const RESET: &str = "\x1b[0m";
+
+/// Surfaces from the outermost to the innermost, as Zed stacks them.
+const SURFACES: [&str; 8] = [
+    "background",
+    "title_bar.background",
+    "panel.background",
+    "surface.background",
+    "elevated_surface.background",
+    "editor.background",
+    "editor.subheader.background",
+    "element.background",
+];
+
+/// 24-bit ANSI escape setting the text color.
+pub fn foreground(color: Color) -> String {
+    let [red, green, blue] = color.rgb();
+    format!("\x1b[38;2;{};{};{}m", red, green, blue)
+}
+
+/// 24-bit ANSI escape setting the cell background.
+pub fn background(color: Color) -> String {
+    let [red, green, blue] = color.rgb();
+    format!("\x1b[48;2;{};{};{}m", red, green, blue)
+}
+
+/// Prints a theme with 24-bit ANSI colors: code samples, the terminal
+/// palette, git and diagnostic colors and the surface hierarchy. Translucent
+/// colors are composited over the editor background, since terminals can't
+/// blend.
+pub struct TerminalPreview<'a> {
+    theme: &'a Theme,
+    style: Value,
+}
+
+impl<'a> TerminalPreview<'a> {
+    pub fn new(theme: &'a Theme) -> Self {
+        let value = serde_json::to_value(theme).unwrap();
+        Self {
+            theme,
+            style: value["style"].clone(),
+        }
+    }
+
+    fn color(&self, key: &str) -> Color {
+        self.style[key]
+            .as_str()
+            .map(Color::from_hex)
+            .unwrap_or(self.theme.foreground)
+            .over(self.theme.background)
+    }
+
+    /// One full-width row on the editor background.
+    fn row(&self, output: &mut String, content: &str, length: usize) {
+        let _ = writeln!(
+            output,
+            "{}{} {}{}{}",
+            background(self.theme.background),
+            foreground(self.theme.foreground),
+            content,
+            " ".repeat(WIDTH.saturating_sub(length + 1)),
+            RESET
+        );
+    }
+
+    fn heading(&self, output: &mut String, title: &str) {
+        self.row(output, "", 0);
+        let text = format!("{}{}{}", foreground(self.theme.accent), title, RESET);
+        self.row(
+            output,
+            &format!("{}{}", text, background(self.theme.background)),
+            title.chars().count(),
+        );
+    }
+
+    fn code(&self, output: &mut String, lines: &[SampleLine]) {
+        let editor = self.theme.background;
+
+        for line in lines {
+            let mut content = String::new();
+            let mut length = 0;
+
+            for (capture, text) in line.iter() {
+                let style = match *capture {
+                    "" => None,
+                    capture => Some(self.theme.highlight(capture)),
+                };
+                let color = style
+                    .as_ref()
+                    .map_or(self.theme.foreground, |style| style.color);
+                let _ = write!(content, "{}", foreground(color.over(editor)));
+                if let Some(color) = style.as_ref().and_then(|style| style.background) {
+                    let _ = write!(content, "{}", background(color.over(editor)));
+                }
+                if style.as_ref().is_some_and(|style| style.italic) {
+                    content.push_str("\x1b[3m");
+                }
+                if style.as_ref().is_some_and(|style| style.bold) {
+                    content.push_str("\x1b[1m");
+                }
+                let _ = write!(content, "{}\x1b[22;23m{}", text, background(editor));
+                length += text.chars().count();
+            }
+
+            self.row(output, &format!("  {}", content), length + 2);
+        }
+    }
+
+    fn samples(&self, output: &mut String, samples: &[(&str, Color)]) {
+        let mut content = String::new();
+        let mut length = 0;
+
+        for (label, color) in samples {
+            let tint = color.with_opacity(0.2).over(self.theme.background);
+            let _ = write!(
+                content,
+                "{}{} {} {}  ",
+                background(tint),
+                foreground(*color),
+                label,
+                background(self.theme.background)
+            );
+            length += label.chars().count() + 4;
+        }
+
+        self.row(output, &format!("  {}", content), length + 2);
+    }
+
+    pub fn render(&self) -> String {
+        let theme = self.theme;
+        let mut output = String::new();
+
+        self.heading(&mut output, &theme.name);
+        self.heading(&mut output, "Rust");
+        self.code(&mut output, sample::RUST);
+        self.heading(&mut output, "TypeScript");
+        self.code(&mut output, sample::TYPESCRIPT);
+
+        self.heading(&mut output, "Terminal");
+        let colors = theme.terminal.colors();
+        for row in colors.chunks(8) {
+            let mut content = String::from("  ");
+            for (_, color) in row {
+                let _ = write!(
+                    content,
+                    "{}      {} ",
+                    background(*color),
+                    background(theme.background)
+                );
+            }
+            self.row(&mut output, &content, 2 + 7 * row.len());
+        }
+
+        self.heading(&mut output, "Git");
+        self.samples(
+            &mut output,
+            &[
+                ("+ added", theme.git.added),
+                ("~ modified", theme.git.modified),
+                ("- removed", theme.git.removed),
+            ],
+        );
+        self.heading(&mut output, "Diagnostics");
+        self.samples(
+            &mut output,
+            &[
+                ("error", theme.diagnostics.error),
+                ("warning", theme.diagnostics.warning),
+                ("info", theme.diagnostics.info),
+            ],
+        );
+
+        self.heading(&mut output, "Surfaces");
+        for (depth, key) in SURFACES.iter().enumerate() {
+            let indent = 2 + depth * 2;
+            let label = format!("{:<width$}", key, width = WIDTH - indent - 12);
+            let content = format!(
+                "{}{}{}{} {} {}",
+                " ".repeat(indent - 1),
+                background(self.color(key)),
+                foreground(self.color("text")),
+                label,
+                self.color(key).to_hex(),
+                background(theme.background),
+            );
+            self.row(&mut output, &content, indent - 1 + label.len() + 9);
+        }
+        self.row(&mut output, "", 0);
+
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_code_and_palettes() {
+        for theme in crate::snowfall().themes {
+            let output = TerminalPreview::new(&theme).render();
+
+            assert!(output.contains("Snowflake"));
+            assert!(output.contains(&foreground(theme.tokens.keywords)));
+            assert!(output.contains(&background(theme.terminal.bright_white)));
+            assert!(output.lines().all(|line| line.ends_with(RESET)));
+        }
+    }
+}
@@ -0,0 +1,244 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    color::Color,
+    colorblind,
+    theme::{
+        Brightness, Theme, ThemeCreatePayload, ThemeDerived, ThemeDiagnostic, ThemeGit,
+        ThemePackage, ThemeTerminal, ThemeTokens,
+    },
+};
+
+/// Named colors from hex values.
+fn named<const N: usize>(colors: [(&str, &str); N]) -> BTreeMap<String, Color> {
+    colors
+        .into_iter()
+        .map(|(name, hex)| (name.to_string(), Color::from_hex(hex)))
+        .collect()
+}
+
+/// Which named color each role is set from, see [`ThemeCreatePayload::names`].
+fn links<const N: usize>(links: [(&str, &str); N]) -> BTreeMap<String, String> {
+    links
+        .into_iter()
+        .map(|(role, name)| (role.to_string(), name.to_string()))
+        .collect()
+}
+
+/// The Snowfall family as published in `themes/theme.json`.
+pub fn snowfall() -> ThemePackage {
+    let colors = named([
+        ("berry", "#FA7583"),
+        ("frost", "#84c4df"),
+        ("glacier", "#7FB2C7"),
+        ("heather", "#B08CBA"),
+        ("lichen", "#ACBDC3"),
+        ("mist", "#91a2a6"),
+        ("moss", "#BDB969"),
+        ("night", "#24272d"),
+        ("sand", "#EBD2A7"),
+        ("sky", "#9BCAFF"),
+        ("slate", "#474c54"),
+    ]);
+    let dark_theme: Theme = Theme::new(ThemeCreatePayload {
+        name: String::from("Snowfall dark"),
+        brightness: Brightness::Dark,
+        accent: colors["frost"],
+        background: colors["night"],
+        foreground: colors["mist"],
+        git: ThemeGit {
+            added: colors["moss"],
+            modified: colors["frost"],
+            removed: colors["berry"],
+        },
+        terminal: ThemeTerminal {
+            black: Color::from_hex("#1c1c1c"),
+            red: Color::from_hex("#cc6666"),
+            green: Color::from_hex("#bdb968"),
+            yellow: Color::from_hex("#f0c674"),
+            blue: Color::from_hex("#81a2be"),
+            magenta: Color::from_hex("#b193ba"),
+            cyan: Color::from_hex("#7fb2c8"),
+            white: Color::from_hex("#c8ccd4"),
+            bright_black: Color::from_hex("#636363"),
+            bright_red: Color::from_hex("#a04041"),
+            bright_green: Color::from_hex("#8b9440"),
+            bright_yellow: colors["sand"],
+            bright_blue: Color::from_hex("#5d7f9a"),
+            bright_magenta: Color::from_hex("#82658c"),
+            bright_cyan: Color::from_hex("#5e8d87"),
+            bright_white: Color::from_hex("#ffffff"),
+        },
+        tokens: ThemeTokens {
+            brackets: colors["glacier"],
+            comment: colors["slate"],
+            strings: colors["moss"],
+            types: colors["lichen"],
+            functions: colors["glacier"],
+            properties: colors["sand"],
+            keywords: colors["heather"],
+            constants: colors["sand"],
+            operators: colors["heather"],
+        },
+        diagnostics: ThemeDiagnostic {
+            error: colors["berry"],
+            warning: colors["sand"],
+            info: colors["sky"],
+        },
+        derived: ThemeDerived::default(),
+        colors,
+        names: links([
+            ("accent", "frost"),
+            ("background", "night"),
+            ("foreground", "mist"),
+            ("git.added", "moss"),
+            ("git.modified", "frost"),
+            ("git.removed", "berry"),
+            ("terminal.bright_yellow", "sand"),
+            ("tokens.brackets", "glacier"),
+            ("tokens.comment", "slate"),
+            ("tokens.strings", "moss"),
+            ("tokens.types", "lichen"),
+            ("tokens.functions", "glacier"),
+            ("tokens.properties", "sand"),
+            ("tokens.keywords", "heather"),
+            ("tokens.constants", "sand"),
+            ("tokens.operators", "heather"),
+            ("diagnostics.error", "berry"),
+            ("diagnostics.warning", "sand"),
+            ("diagnostics.info", "sky"),
+        ]),
+    })
+    .unwrap();
+
+    let colors = named([
+        ("amber", "#d59225"),
+        ("ash", "#bdc1c8"),
+        ("berry", "#FA7583"),
+        ("coral", "#FF8F9B"),
+        ("ember", "#ec9c62"),
+        ("fern", "#8ACB6B"),
+        ("fjord", "#50afce"),
+        ("frost", "#88C0D0"),
+        ("granite", "#5C6165"),
+        ("heather", "#a25cb5"),
+        ("lichen", "#ACBDC3"),
+        ("pine", "#85B300"),
+        ("sky", "#9BCAFF"),
+        ("snow", "#ffffff"),
+    ]);
+    let light_theme = Theme::new(ThemeCreatePayload {
+        name: String::from("Snowfall light"),
+        brightness: Brightness::Light,
+        background: colors["snow"],
+        foreground: colors["granite"],
+        accent: colors["frost"],
+        tokens: ThemeTokens {
+            brackets: colors["fjord"],
+            comment: colors["ash"],      // base03
+            strings: colors["pine"],     // base0B
+            constants: colors["amber"],  // base09
+            keywords: colors["heather"], // base0E
+            operators: colors["lichen"], // base05,
+            properties: colors["amber"], // base0D
+            types: colors["granite"],    // base0C
+            functions: colors["fjord"],
+        },
+        git: ThemeGit {
+            added: colors["fern"],
+            modified: colors["frost"],
+            removed: colors["coral"],
+        },
+        diagnostics: ThemeDiagnostic {
+            error: colors["berry"],
+            warning: colors["ember"],
+            info: colors["sky"],
+        },
+        terminal: ThemeTerminal {
+            black: Color::from_hex("#1c1c1c"),
+            red: Color::from_hex("#cc6666"),
+            green: Color::from_hex("#bdb968"),
+            yellow: Color::from_hex("#f0c674"),
+            blue: Color::from_hex("#81a2be"),
+            magenta: Color::from_hex("#b193ba"),
+            cyan: Color::from_hex("#7fb2c8"),
+            white: Color::from_hex("#c8ccd4"),
+            bright_black: Color::from_hex("#636363"),
+            bright_red: Color::from_hex("#a04041"),
+            bright_green: Color::from_hex("#8b9440"),
+            bright_yellow: colors["ember"],
+            bright_blue: Color::from_hex("#5d7f9a"),
+            bright_magenta: Color::from_hex("#82658c"),
+            bright_cyan: Color::from_hex("#5e8d87"),
+            bright_white: colors["snow"],
+        },
+        derived: ThemeDerived::default(),
+        colors,
+        names: links([
+            ("accent", "frost"),
+            ("background", "snow"),
+            ("foreground", "granite"),
+            ("tokens.brackets", "fjord"),
+            ("tokens.comment", "ash"),
+            ("tokens.strings", "pine"),
+            ("tokens.constants", "amber"),
+            ("tokens.keywords", "heather"),
+            ("tokens.operators", "lichen"),
+            ("tokens.properties", "amber"),
+            ("tokens.types", "granite"),
+            ("tokens.functions", "fjord"),
+            ("git.added", "fern"),
+            ("git.modified", "frost"),
+            ("git.removed", "coral"),
+            ("diagnostics.error", "berry"),
+            ("diagnostics.warning", "ember"),
+            ("diagnostics.info", "sky"),
+            ("terminal.bright_yellow", "ember"),
+            ("terminal.bright_white", "snow"),
+        ]),
+    })
+    .unwrap();
+
+    ThemePackage {
+        name: "Snowfall".into(),
+        author: "freethinkel".into(),
+        themes: vec![
+            dark_theme.clone(),
+            dark_theme.clone().to_bg_syntax(),
+            light_theme.clone(),
+            light_theme.clone().to_bg_syntax(),
+            colorblind::safe_variant(&dark_theme).unwrap(),
+            colorblind::safe_variant(&light_theme).unwrap(),
+        ],
+    }
+}
+
+/// Seasonal variants of the dark and light themes, built from transforms.
+/// They are not part of the published family.
+pub fn seasons() -> ThemePackage {
+    let package = snowfall();
+    let (dark_theme, light_theme) = (&package.themes[0], &package.themes[2]);
+
+    ThemePackage {
+        name: "Snowfall seasons".into(),
+        author: package.author.clone(),
+        themes: vec![
+            Theme {
+                name: "Snowfall dusk".into(),
+                ..dark_theme
+                    .shift_temperature(0.02)
+                    .and_then(|theme| theme.rotate_hue(-10.0))
+                    .and_then(|theme| theme.tint_background(300.0, 0.015))
+                    .unwrap()
+            },
+            Theme {
+                name: "Snowfall frost".into(),
+                ..light_theme
+                    .shift_temperature(-0.015)
+                    .and_then(|theme| theme.scale_chroma(0.85))
+                    .and_then(|theme| theme.tint_background(230.0, 0.012))
+                    .unwrap()
+            },
+        ],
+    }
+}
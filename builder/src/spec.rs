@@ -0,0 +1,283 @@
+use std::{collections::BTreeMap, fs, io, path::Path};
+
+use toml::{Table, Value};
+
+use crate::{color::Color, expr::Palette, theme::ThemeCreatePayload};
+
+/// Names a `[colors]` entry can't take, because roles already use them.
+const RESERVED: [&str; 8] = [
+    "accent",
+    "background",
+    "foreground",
+    "secondary_bg",
+    "border",
+    "doc_comment",
+    "bg",
+    "fg",
+];
+
+/// Reads a palette spec: a TOML file with the fields of `ThemeCreatePayload`.
+pub fn read(path: &Path) -> Result<ThemeCreatePayload, String> {
+    let source = fs::read_to_string(path)
+        .map_err(|error| format!("Error reading {}: {}", path.display(), error))?;
+
+    from_str(&source).map_err(|error| format!("{}:\n{}", path.display(), error))
+}
+
+/// Parses a palette spec. Any color may be an expression such as
+/// `mix(accent, background, 0.9)` or the name of another color (see
+/// [`crate::expr`]), including the named colors of the `[colors]` table,
+/// which are referred to by their bare name (`frost`). The optional
+/// `[derived]` table overrides how `secondary_bg`, `border` and
+/// `doc_comment` are derived.
+pub fn from_str(source: &str) -> Result<ThemeCreatePayload, String> {
+    let mut table: Table = toml::from_str(source).map_err(|error| error.to_string())?;
+
+    let mut palette = Palette::new();
+    // Definitions and where their value goes back into the table. Derived
+    // colors stay expressions, evaluated for each theme.
+    let mut definitions: Vec<(String, Vec<String>, &Value)> = vec![];
+    for (key, value) in &table {
+        match (key.as_str(), value) {
+            ("name" | "brightness", _) => {}
+            ("derived", Value::Table(group)) => {
+                for (name, value) in group {
+                    definitions.push((name.clone(), vec![], value));
+                }
+            }
+            ("colors", Value::Table(group)) => {
+                for (name, value) in group {
+                    if RESERVED.contains(&name.as_str()) {
+                        return Err(format!("colors.{}: `{}` is already a role", name, name));
+                    }
+                    definitions.push((name.clone(), vec![key.clone(), name.clone()], value));
+                }
+            }
+            (_, Value::Table(group)) => {
+                for (name, value) in group {
+                    let path = vec![key.clone(), name.clone()];
+                    definitions.push((path.join("."), path, value));
+                }
+            }
+            (_, value) => definitions.push((key.clone(), vec![key.clone()], value)),
+        }
+    }
+
+    let mut targets = vec![];
+    for (name, path, value) in &definitions {
+        let Value::String(source) = value else {
+            continue;
+        };
+        palette.define(name.clone(), source);
+        if !path.is_empty() {
+            targets.push((name.clone(), path.clone()));
+        }
+    }
+    // Plain hex stays valid with or without the `#`, unless it's a name:
+    // `face` is the color named face, not `#ffaacc`. Roles set to a named
+    // color remember it, to be written back as that name.
+    let named = definitions
+        .iter()
+        .filter(|(_, path, _)| path.first().is_some_and(|group| group == "colors"))
+        .map(|(name, _, _)| name.as_str())
+        .collect::<Vec<_>>();
+    let mut names = BTreeMap::new();
+    for (name, path, value) in &definitions {
+        let Some(source) = value.as_str() else {
+            continue;
+        };
+        let reference = match source.trim().starts_with('#') {
+            true => None,
+            false => palette.lookup(source.trim()).ok(),
+        };
+        if let Some(target) = reference.filter(|target| target != name) {
+            let role = path.first().is_some_and(|group| group != "colors");
+            if role && named.contains(&target) {
+                names.insert(name.clone(), target.to_string());
+            }
+            continue;
+        }
+        if let Some(color) = Color::parse(source) {
+            palette.define(name.clone(), color.to_hex());
+        }
+    }
+
+    let values = palette.evaluate().map_err(|error| error.to_string())?;
+    for (name, path) in targets {
+        let hex = Value::String(values[&name].to_hex());
+        match &path[..] {
+            [group, name] => table[group][name] = hex,
+            [name] => table[name] = hex,
+            _ => unreachable!(),
+        }
+    }
+
+    let mut payload: ThemeCreatePayload = table.try_into().map_err(|error| error.to_string())?;
+    payload.names = names;
+    Ok(payload)
+}
+
+/// The payload as a spec, with roles set from a named color written as that
+/// name, so editing the named color changes them all.
+pub fn to_string(payload: &ThemeCreatePayload) -> Result<String, String> {
+    let mut table = Table::try_from(payload).map_err(|error| error.to_string())?;
+    for (role, name) in payload.references() {
+        match role.split_once('.') {
+            Some((group, role)) => table[group][role] = Value::String(name),
+            None => table[&role] = Value::String(name),
+        }
+    }
+    toml::to_string(&table).map_err(|error| error.to_string())
+}
+
+pub fn write(path: &Path, payload: &ThemeCreatePayload) -> io::Result<()> {
+    fs::write(path, to_string(payload).map_err(io::Error::other)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_through_toml() {
+        let theme = &crate::snowfall().themes[0];
+        let payload = theme.payload();
+        let source = to_string(&payload).unwrap();
+        let parsed = from_str(&source).unwrap();
+
+        assert!(source.contains("accent = \"frost\""));
+        assert!(source.contains("frost = \"#84c4df\""));
+        assert!(source.contains("bright_white = \"#ffffff\""));
+        assert_eq!(to_string(&parsed).unwrap(), source);
+    }
+
+    #[test]
+    fn named_colors_change_every_role_using_them() {
+        let theme = &crate::snowfall().themes[0];
+        let source = to_string(&theme.payload())
+            .unwrap()
+            .replace("sand = \"#ebd2a7\"", "sand = \"#e0c080\"");
+        let payload = from_str(&source).unwrap();
+        let sand = Color::from_hex("#e0c080");
+
+        assert_eq!(payload.colors["sand"], sand);
+        assert_eq!(payload.tokens.properties, sand);
+        assert_eq!(payload.tokens.constants, sand);
+        assert_eq!(payload.diagnostics.warning, sand);
+        assert_eq!(payload.terminal.bright_yellow, sand);
+        assert!(payload
+            .references()
+            .contains(&("diagnostics.warning".into(), "sand".into())));
+
+        let clash = source.replace("sand = \"#e0c080\"", "accent = \"#e0c080\"");
+        assert!(from_str(&clash).err().unwrap().contains("already a role"));
+    }
+
+    #[test]
+    fn writes_back_the_names_roles_were_set_from() {
+        let theme = &crate::snowfall().themes[0];
+        let source = to_string(&theme.payload())
+            .unwrap()
+            .replace(
+                "frost = \"#84c4df\"",
+                "frost = \"#84c4df\"\nice = \"#84c4df\"",
+            )
+            .replace("accent = \"frost\"", "accent = \"ice\"")
+            .replace("modified = \"frost\"", "modified = \"#84c4df\"");
+        let payload = from_str(&source).unwrap();
+        let written = to_string(&payload).unwrap();
+
+        assert!(written.contains("accent = \"ice\"\n"));
+        assert!(written.contains("modified = \"#84c4df\"\n"));
+        assert!(!payload
+            .references()
+            .contains(&("git.modified".into(), "frost".into())));
+    }
+
+    #[test]
+    fn names_win_over_plain_hex() {
+        let theme = &crate::snowfall().themes[0];
+        let source = to_string(&theme.payload())
+            .unwrap()
+            .replace("sand", "decade")
+            .replace("frost", "face")
+            .replace("\"#ebd2a7\"", "\"ebd2a7\"");
+        let payload = from_str(&source).unwrap();
+
+        assert_eq!(payload.colors["decade"], Color::from_hex("#ebd2a7"));
+        assert_eq!(payload.tokens.properties, payload.colors["decade"]);
+        assert_eq!(payload.accent, theme.accent);
+        assert_eq!(payload.git.modified, theme.git.modified);
+    }
+
+    #[test]
+    fn evaluates_expressions() {
+        let theme = &crate::snowfall().themes[0];
+        let source = to_string(&theme.payload())
+            .unwrap()
+            .replace("comment = \"slate\"", "comment = \"mix(fg, bg, 0.4)\"")
+            .replace("modified = \"frost\"", "modified = \"accent\"")
+            .replace(
+                "border = \"mix(accent, background, 0.9)\"",
+                "border = \"alpha(git.modified, 0.3)\"",
+            );
+        let payload = from_str(&source).unwrap();
+
+        assert_eq!(
+            payload.tokens.comment,
+            theme.foreground.mix(theme.background, 0.4)
+        );
+        assert_eq!(payload.git.modified, theme.accent);
+        assert_eq!(
+            crate::Theme::new(payload).unwrap().border(),
+            theme.accent.with_opacity(0.3)
+        );
+    }
+
+    #[test]
+    fn reports_errors_in_expressions() {
+        let theme = &crate::snowfall().themes[0];
+        let source = to_string(&theme.payload()).unwrap();
+
+        let cycle = source
+            .replace("accent = \"frost\"", "accent = \"git.modified\"")
+            .replace("modified = \"frost\"", "modified = \"accent\"");
+        assert!(from_str(&cycle)
+            .err()
+            .unwrap()
+            .contains("cycle: accent -> git.modified -> accent"));
+
+        let typo = source.replace(
+            "doc_comment = \"mix(tokens.comment, foreground, 0.5)\"",
+            "doc_comment = \"mix(tokens.coment, foreground, 0.5)\"",
+        );
+        assert!(from_str(&typo)
+            .err()
+            .unwrap()
+            .starts_with("doc_comment: unknown color `tokens.coment`"));
+    }
+
+    #[test]
+    fn themes_reject_derived_colors_that_fail() {
+        let mut payload = crate::snowfall().themes[0].payload();
+        payload.derived.border = "mix(acent, background, 0.9)".into();
+
+        assert!(crate::Theme::new(payload)
+            .err()
+            .unwrap()
+            .starts_with("Snowfall dark: border: unknown color `acent`"));
+    }
+
+    #[test]
+    fn transforms_report_derived_colors_that_fail() {
+        let mut theme = crate::snowfall().themes[0].clone();
+        theme.derived.border = "mix(acent, background, 0.9)".into();
+
+        assert!(theme
+            .rotate_hue(10.0)
+            .err()
+            .unwrap()
+            .contains("unknown color `acent`"));
+        assert!(theme.tint_background(300.0, 0.01).is_err());
+    }
+}
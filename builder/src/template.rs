@@ -0,0 +1,277 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use serde_json::{json, Map, Value};
+use tera::{Context, Tera};
+
+use crate::{
+    color::Color,
+    export::{OutputFile, ThemeExporter},
+    theme::{Brightness, Theme, ThemePackage},
+};
+
+const TEMPLATE: &str = "template";
+
+/// Renders a user template once per theme, into `dist/<name>/<theme>.<ext>`.
+/// A template named `statusbar.conf.tera` has the name `statusbar` and the
+/// extension `conf`.
+///
+/// Templates use Tera syntax (`{{ tokens.keywords | alpha(value=0.5) }}`,
+/// `{% for name, color in terminal %}`) with the context built by
+/// [`context`], plus the color filters `hex`, `bare`, `rgb`, `alpha`, `mix`,
+/// `lighten` and `darken`.
+pub struct TemplateExporter {
+    name: String,
+    extension: String,
+    tera: Tera,
+    files: Vec<OutputFile>,
+}
+
+impl TemplateExporter {
+    /// Reads a template and renders it once for every theme of `package`, so
+    /// errors that only show up at render time, like an undefined variable,
+    /// are reported here rather than while exporting.
+    pub fn new(path: &Path, package: &ThemePackage) -> Result<Self, String> {
+        let source = fs::read_to_string(path)
+            .map_err(|error| format!("Error reading {}: {}", path.display(), error))?;
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+        let file_name = file_name.strip_suffix(".tera").unwrap_or(&file_name);
+        let (name, extension) = file_name
+            .rsplit_once('.')
+            .ok_or_else(|| format!("{}: expected a name like status.conf.tera", path.display()))?;
+
+        Self::from_source(name, extension, &source, package)
+            .map_err(|error| format!("{}: {}", path.display(), error))
+    }
+
+    /// A template from its source, rendered once for every theme of
+    /// `package`. Those renders are what [`export`](ThemeExporter::export)
+    /// writes, so a template that fails to render fails here.
+    pub fn from_source(
+        name: &str,
+        extension: &str,
+        source: &str,
+        package: &ThemePackage,
+    ) -> Result<Self, String> {
+        let mut tera = Tera::default();
+        tera.add_raw_template(TEMPLATE, source)
+            .map_err(|error| describe(&error))?;
+        tera.register_filter("hex", |value: &Value, _: &HashMap<String, Value>| {
+            Ok(color(value, "hex")?.to_hex().into())
+        });
+        tera.register_filter("bare", |value: &Value, _: &HashMap<String, Value>| {
+            Ok(color(value, "bare")?.to_hex()[1..].into())
+        });
+        tera.register_filter("rgb", |value: &Value, _: &HashMap<String, Value>| {
+            Ok(color(value, "rgb")?.to_css().into())
+        });
+        tera.register_filter("alpha", |value: &Value, args: &HashMap<String, Value>| {
+            let opacity = number(args, "value", "alpha")?;
+            Ok(color(value, "alpha")?.with_opacity(opacity).to_hex().into())
+        });
+        tera.register_filter("mix", |value: &Value, args: &HashMap<String, Value>| {
+            let other = args
+                .get("with")
+                .ok_or("Filter `mix` expected an arg called `with`")?;
+            let amount = number(args, "amount", "mix")?;
+            Ok(color(value, "mix")?
+                .mix(color(other, "mix")?, amount)
+                .to_hex()
+                .into())
+        });
+        tera.register_filter("lighten", |value: &Value, args: &HashMap<String, Value>| {
+            let amount = number(args, "amount", "lighten")?;
+            Ok(color(value, "lighten")?
+                .oklch_lighten(amount)
+                .to_hex()
+                .into())
+        });
+        tera.register_filter("darken", |value: &Value, args: &HashMap<String, Value>| {
+            let amount = number(args, "amount", "darken")?;
+            Ok(color(value, "darken")?
+                .oklch_lighten(-amount)
+                .to_hex()
+                .into())
+        });
+
+        let mut exporter = Self {
+            name: name.to_string(),
+            extension: extension.to_string(),
+            tera,
+            files: vec![],
+        };
+        for theme in &package.themes {
+            let contents = exporter
+                .render(package, theme)
+                .map_err(|error| format!("{}: {}", theme.name, error))?;
+            exporter.files.push(OutputFile {
+                path: Path::new("dist")
+                    .join(name)
+                    .join(format!("{}.{}", theme.slug(), extension)),
+                contents,
+            });
+        }
+        Ok(exporter)
+    }
+
+    pub fn render(&self, package: &ThemePackage, theme: &Theme) -> Result<String, String> {
+        let context = Context::from_value(context(package, theme)).unwrap();
+        self.tera
+            .render(TEMPLATE, &context)
+            .map_err(|error| describe(&error))
+    }
+}
+
+impl ThemeExporter for TemplateExporter {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn extension(&self) -> &str {
+        &self.extension
+    }
+
+    /// The files rendered for the package the template was read with.
+    fn export(&self, _package: &ThemePackage) -> Vec<OutputFile> {
+        self.files.clone()
+    }
+}
+
+/// Tera's errors keep the useful part in their sources.
+fn describe(error: &tera::Error) -> String {
+    let mut message = error.to_string();
+    let mut source = std::error::Error::source(error);
+    while let Some(error) = source {
+        message.push_str(&format!(": {}", error));
+        source = error.source();
+    }
+    message
+}
+
+fn color(value: &Value, filter: &str) -> tera::Result<Color> {
+    value
+        .as_str()
+        .and_then(Color::parse)
+        .ok_or_else(|| format!("Filter `{}` expected a color, got {}", filter, value).into())
+}
+
+fn number(args: &HashMap<String, Value>, name: &str, filter: &str) -> tera::Result<f32> {
+    args.get(name)
+        .and_then(Value::as_f64)
+        .map(|value| value as f32)
+        .ok_or_else(|| {
+            format!(
+                "Filter `{}` expected a number arg called `{}`",
+                filter, name
+            )
+            .into()
+        })
+}
+
+/// What templates see: the palette as hex strings grouped like the model
+/// (`tokens.keywords`, `terminal.bright_red`, `git.added`,
+/// `diagnostics.error`), the derived `secondary_bg` and `border`, the named
+/// `colors` and which of them each role was set from (`names["tokens.keywords"]`),
+/// and the theme and package names.
+pub fn context(package: &ThemePackage, theme: &Theme) -> Value {
+    let group = |colors: Vec<(&str, Color)>| {
+        colors
+            .into_iter()
+            .map(|(name, color)| (name.to_string(), json!(color)))
+            .collect::<Map<_, _>>()
+    };
+
+    json!({
+        "package": { "name": package.name, "author": package.author },
+        "name": theme.name,
+        "slug": theme.slug(),
+        "appearance": match theme.brightness {
+            Brightness::Dark => "dark",
+            Brightness::Light => "light",
+        },
+        "background_syntax": theme.is_background_syntax,
+        "accent": theme.accent,
+        "background": theme.background,
+        "foreground": theme.foreground,
+        "secondary_bg": theme.secondary_bg(),
+        "border": theme.border(),
+        "tokens": group(theme.tokens.colors()),
+        "terminal": group(theme.terminal.colors()),
+        "git": group(theme.git.colors()),
+        "diagnostics": group(theme.diagnostics.colors()),
+        "colors": theme.colors,
+        "names": theme.payload().references().into_iter().collect::<HashMap<_, _>>(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(source: &str) -> Result<String, String> {
+        let mut package = crate::snowfall();
+        package.themes.truncate(1);
+        TemplateExporter::from_source("test", "txt", source, &package)?
+            .render(&package, &package.themes[0])
+    }
+
+    #[test]
+    fn exposes_the_palette_and_filters() {
+        assert_eq!(
+            render("{{ name }} {{ tokens.keywords }}").unwrap(),
+            "Snowfall dark #b08cba"
+        );
+        assert_eq!(render("{{ background | bare }}").unwrap(), "24272d");
+        assert_eq!(
+            render("{{ accent | alpha(value=0.5) | rgb }}").unwrap(),
+            "rgba(132, 196, 223, 0.498)"
+        );
+        assert_eq!(
+            render("{{ background | mix(with=\"#ffffff\", amount=1.0) }}").unwrap(),
+            "#ffffff"
+        );
+        assert_eq!(
+            render("{% for name, color in git %}{{ name }}={{ color }} {% endfor %}").unwrap(),
+            "added=#bdb969 modified=#84c4df removed=#fa7583 "
+        );
+        assert_eq!(
+            render("{{ names[\"tokens.keywords\"] }} {{ colors.heather }}").unwrap(),
+            "heather #b08cba"
+        );
+        assert_eq!(
+            render("{{ secondary_bg }}").unwrap(),
+            crate::snowfall().themes[0].secondary_bg().to_hex()
+        );
+    }
+
+    #[test]
+    fn lightens_in_oklch() {
+        let lighter = render("{{ background | lighten(amount=0.1) }}").unwrap();
+        let darker = render("{{ background | darken(amount=0.1) }}").unwrap();
+
+        assert!(Color::from_hex(&lighter).to_oklch().0 > Color::from_hex("#24272d").to_oklch().0);
+        assert!(Color::from_hex(&darker).to_oklch().0 < Color::from_hex("#24272d").to_oklch().0);
+    }
+
+    #[test]
+    fn reports_render_errors_when_reading_templates() {
+        let package = crate::snowfall();
+        let path = std::env::temp_dir().join(format!(
+            "snowfall-undefined-{}.txt.tera",
+            std::process::id()
+        ));
+        fs::write(&path, "{{ tokens.keywords }} {{ nope }}").unwrap();
+        let error = TemplateExporter::new(&path, &package).err().unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert!(error.contains(".txt.tera: Snowfall dark: "));
+        assert!(error.contains("nope"));
+    }
+
+    #[test]
+    fn reports_template_errors() {
+        assert!(render("{{ tokens.nope }}").unwrap_err().contains("nope"));
+        assert!(render("{{ name | mix(amount=0.5) }}")
+            .unwrap_err()
+            .contains("with"));
+    }
+}
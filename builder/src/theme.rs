@@ -1,14 +1,17 @@
-use std::fmt::Display;
+use std::{collections::BTreeMap, fmt::Display};
 
-use crate::color::Color;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone)]
+use crate::{color::Color, expr::Palette};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Brightness {
     Light,
     Dark,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ThemeTokens {
     pub brackets: Color,
     pub comment: Color,
@@ -21,21 +24,21 @@ pub struct ThemeTokens {
     pub operators: Color,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ThemeDiagnostic {
     pub error: Color,
     pub warning: Color,
     pub info: Color,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ThemeGit {
     pub added: Color,
     pub modified: Color,
     pub removed: Color,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ThemeTerminal {
     pub black: Color,
     pub red: Color,
@@ -55,6 +58,35 @@ pub struct ThemeTerminal {
     pub bright_white: Color,
 }
 
+/// Surface colors derived from the palette, as color expressions (see
+/// [`crate::expr`]) that can name any palette color, e.g. `tokens.comment`.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThemeDerived {
+    pub secondary_bg: String,
+    pub border: String,
+    /// `comment.doc`.
+    pub doc_comment: String,
+}
+
+impl Default for ThemeDerived {
+    fn default() -> Self {
+        Self {
+            secondary_bg: "mix(background, foreground, 0.025)".into(),
+            border: "mix(accent, background, 0.9)".into(),
+            doc_comment: "mix(tokens.comment, foreground, 0.5)".into(),
+        }
+    }
+}
+
+/// The [`ThemeDerived`] expressions evaluated against the theme's palette.
+#[derive(Clone)]
+pub struct ThemeSurfaces {
+    pub secondary_bg: Color,
+    pub border: Color,
+    pub doc_comment: Color,
+}
+
 #[derive(Clone)]
 pub struct Theme {
     pub is_background_syntax: bool,
@@ -63,10 +95,15 @@ pub struct Theme {
     pub accent: Color,
     pub background: Color,
     pub foreground: Color,
+    pub colors: BTreeMap<String, Color>,
+    /// See [`ThemeCreatePayload::names`].
+    pub names: BTreeMap<String, String>,
     pub tokens: ThemeTokens,
     pub terminal: ThemeTerminal,
     pub diagnostics: ThemeDiagnostic,
     pub git: ThemeGit,
+    pub derived: ThemeDerived,
+    pub surfaces: ThemeSurfaces,
 }
 
 pub struct ThemePackage {
@@ -75,32 +112,118 @@ pub struct ThemePackage {
     pub themes: Vec<Theme>,
 }
 
+/// Everything needed to create a theme. Palette spec files are this struct
+/// as TOML.
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ThemeCreatePayload {
     pub name: String,
     pub brightness: Brightness,
     pub accent: Color,
     pub background: Color,
     pub foreground: Color,
+    /// Named base colors roles can be set from, e.g. `frost` or `pine`.
+    /// Changing one changes every role that uses it.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub colors: BTreeMap<String, Color>,
+    /// The named color each role was set from, by dotted role name, e.g.
+    /// `git.modified` to `frost`. Specs record it as the role's value.
+    #[serde(skip)]
+    pub names: BTreeMap<String, String>,
     pub tokens: ThemeTokens,
     pub terminal: ThemeTerminal,
     pub diagnostics: ThemeDiagnostic,
     pub git: ThemeGit,
+    #[serde(default)]
+    pub derived: ThemeDerived,
+}
+
+impl ThemeCreatePayload {
+    /// Every role with its dotted name: `accent`, `background`, `foreground`,
+    /// `tokens.*`, `terminal.*`, `git.*` and `diagnostics.*`.
+    pub fn roles(&self) -> Vec<(String, Color)> {
+        let mut roles = vec![
+            ("accent".to_string(), self.accent),
+            ("background".to_string(), self.background),
+            ("foreground".to_string(), self.foreground),
+        ];
+        for (group, colors) in [
+            ("tokens", self.tokens.colors()),
+            ("terminal", self.terminal.colors()),
+            ("git", self.git.colors()),
+            ("diagnostics", self.diagnostics.colors()),
+        ] {
+            for (name, color) in colors {
+                roles.push((format!("{}.{}", group, name), color));
+            }
+        }
+        roles
+    }
+
+    /// The named color each role was set from, e.g. `("git.modified",
+    /// "frost")`, as recorded in [`names`](Self::names). Roles set to a
+    /// plain color are left out, even when it equals a named color.
+    pub fn references(&self) -> Vec<(String, String)> {
+        self.names
+            .iter()
+            .filter(|(_, name)| self.colors.contains_key(*name))
+            .map(|(role, name)| (role.clone(), name.clone()))
+            .collect()
+    }
+
+    /// Every color as a definition: the named colors under their own names,
+    /// the [`roles`](Self::roles), and the derived `secondary_bg`, `border`
+    /// and `doc_comment`.
+    pub fn palette(&self) -> Palette {
+        let mut palette = Palette::new();
+        for (name, color) in &self.colors {
+            palette.define(name, color.to_hex());
+        }
+        for (role, color) in self.roles() {
+            palette.define(role, color.to_hex());
+        }
+        palette.define("secondary_bg", &self.derived.secondary_bg);
+        palette.define("border", &self.derived.border);
+        palette.define("doc_comment", &self.derived.doc_comment);
+        palette
+    }
+
+    /// The derived colors, or the first expression that fails to evaluate.
+    pub fn surfaces(&self) -> Result<ThemeSurfaces, String> {
+        let palette = self.palette();
+        let get = |name| {
+            palette
+                .get(name)
+                .map_err(|error| format!("{}: {}", self.name, error))
+        };
+        Ok(ThemeSurfaces {
+            secondary_bg: get("secondary_bg")?,
+            border: get("border")?,
+            doc_comment: get("doc_comment")?,
+        })
+    }
 }
 
 impl Theme {
-    pub fn new(payload: ThemeCreatePayload) -> Self {
-        Theme {
+    /// Fails when a derived color doesn't evaluate, e.g. it names an unknown
+    /// color.
+    pub fn new(payload: ThemeCreatePayload) -> Result<Self, String> {
+        let surfaces = payload.surfaces()?;
+        Ok(Theme {
             is_background_syntax: false,
             name: payload.name,
             brightness: payload.brightness,
             accent: payload.accent,
             background: payload.background,
             foreground: payload.foreground,
+            colors: payload.colors,
+            names: payload.names,
             tokens: payload.tokens,
             terminal: payload.terminal,
             diagnostics: payload.diagnostics,
             git: payload.git,
-        }
+            derived: payload.derived,
+            surfaces,
+        })
     }
     pub fn to_bg_syntax(&self) -> Self {
         Theme {
@@ -110,10 +233,14 @@ impl Theme {
             accent: self.accent,
             background: self.background,
             foreground: self.foreground,
+            colors: self.colors.clone(),
+            names: self.names.clone(),
             tokens: self.tokens.clone(),
             terminal: self.terminal.clone(),
             diagnostics: self.diagnostics.clone(),
             git: self.git.clone(),
+            derived: self.derived.clone(),
+            surfaces: self.surfaces.clone(),
         }
     }
 }
@@ -131,12 +258,217 @@ impl Display for Theme {
     }
 }
 
+impl ThemeTokens {
+    pub fn map(&self, f: impl Fn(Color) -> Color) -> Self {
+        ThemeTokens {
+            brackets: f(self.brackets),
+            comment: f(self.comment),
+            strings: f(self.strings),
+            types: f(self.types),
+            functions: f(self.functions),
+            properties: f(self.properties),
+            keywords: f(self.keywords),
+            constants: f(self.constants),
+            operators: f(self.operators),
+        }
+    }
+
+    pub fn colors(&self) -> Vec<(&'static str, Color)> {
+        vec![
+            ("brackets", self.brackets),
+            ("comment", self.comment),
+            ("strings", self.strings),
+            ("types", self.types),
+            ("functions", self.functions),
+            ("properties", self.properties),
+            ("keywords", self.keywords),
+            ("constants", self.constants),
+            ("operators", self.operators),
+        ]
+    }
+}
+
+impl ThemeDiagnostic {
+    pub fn map(&self, f: impl Fn(Color) -> Color) -> Self {
+        ThemeDiagnostic {
+            error: f(self.error),
+            warning: f(self.warning),
+            info: f(self.info),
+        }
+    }
+
+    pub fn colors(&self) -> Vec<(&'static str, Color)> {
+        vec![
+            ("error", self.error),
+            ("warning", self.warning),
+            ("info", self.info),
+        ]
+    }
+}
+
+impl ThemeGit {
+    pub fn map(&self, f: impl Fn(Color) -> Color) -> Self {
+        ThemeGit {
+            added: f(self.added),
+            modified: f(self.modified),
+            removed: f(self.removed),
+        }
+    }
+
+    pub fn colors(&self) -> Vec<(&'static str, Color)> {
+        vec![
+            ("added", self.added),
+            ("modified", self.modified),
+            ("removed", self.removed),
+        ]
+    }
+}
+
+impl ThemeTerminal {
+    pub fn map(&self, f: impl Fn(Color) -> Color) -> Self {
+        ThemeTerminal {
+            black: f(self.black),
+            red: f(self.red),
+            green: f(self.green),
+            yellow: f(self.yellow),
+            blue: f(self.blue),
+            magenta: f(self.magenta),
+            cyan: f(self.cyan),
+            white: f(self.white),
+            bright_black: f(self.bright_black),
+            bright_red: f(self.bright_red),
+            bright_green: f(self.bright_green),
+            bright_yellow: f(self.bright_yellow),
+            bright_blue: f(self.bright_blue),
+            bright_magenta: f(self.bright_magenta),
+            bright_cyan: f(self.bright_cyan),
+            bright_white: f(self.bright_white),
+        }
+    }
+
+    pub fn colors(&self) -> Vec<(&'static str, Color)> {
+        vec![
+            ("black", self.black),
+            ("red", self.red),
+            ("green", self.green),
+            ("yellow", self.yellow),
+            ("blue", self.blue),
+            ("magenta", self.magenta),
+            ("cyan", self.cyan),
+            ("white", self.white),
+            ("bright_black", self.bright_black),
+            ("bright_red", self.bright_red),
+            ("bright_green", self.bright_green),
+            ("bright_yellow", self.bright_yellow),
+            ("bright_blue", self.bright_blue),
+            ("bright_magenta", self.bright_magenta),
+            ("bright_cyan", self.bright_cyan),
+            ("bright_white", self.bright_white),
+        ]
+    }
+}
+
 impl Theme {
+    /// File-name friendly version of the theme name, e.g. `snowfall_dark_background`.
+    pub fn slug(&self) -> String {
+        self.name
+            .to_lowercase()
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join("_")
+    }
+
+    /// Copy of the theme with `f` applied to every palette color. Fails when
+    /// a derived color no longer evaluates.
+    pub fn map_colors(&self, name: String, f: impl Fn(Color) -> Color) -> Result<Self, String> {
+        let mut theme = Theme {
+            is_background_syntax: self.is_background_syntax,
+            name,
+            brightness: self.brightness.clone(),
+            accent: f(self.accent),
+            background: f(self.background),
+            foreground: f(self.foreground),
+            colors: self
+                .colors
+                .iter()
+                .map(|(name, color)| (name.clone(), f(*color)))
+                .collect(),
+            names: self.names.clone(),
+            tokens: self.tokens.map(&f),
+            terminal: self.terminal.map(&f),
+            diagnostics: self.diagnostics.map(&f),
+            git: self.git.map(&f),
+            derived: self.derived.clone(),
+            surfaces: self.surfaces.clone(),
+        };
+        theme.update_surfaces()?;
+        Ok(theme)
+    }
+
+    /// Copy with every color's OKLCH hue rotated by `degrees`.
+    pub fn rotate_hue(&self, degrees: f32) -> Result<Self, String> {
+        self.map_colors(self.name.clone(), |color| color.rotate_hue(degrees))
+    }
+
+    /// Copy with every color's white point shifted, positive is warmer. Around
+    /// `0.02` is a gentle shift.
+    pub fn shift_temperature(&self, amount: f32) -> Result<Self, String> {
+        self.map_colors(self.name.clone(), |color| color.shift_temperature(amount))
+    }
+
+    /// Copy with every color's OKLCH chroma multiplied by `factor`.
+    pub fn scale_chroma(&self, factor: f32) -> Result<Self, String> {
+        self.map_colors(self.name.clone(), |color| color.scale_chroma(factor))
+    }
+
+    /// Copy whose background takes the given OKLCH hue and chroma, keeping its
+    /// lightness. Surfaces derived from the background follow along.
+    pub fn tint_background(&self, hue: f32, chroma: f32) -> Result<Self, String> {
+        let (lightness, _, _) = self.background.to_oklch();
+        let alpha = (self.background.alpha() * 255.0).round() as u8;
+
+        let mut theme = Theme {
+            background: Color::from_oklch(lightness, chroma, hue, alpha),
+            ..self.clone()
+        };
+        // The background no longer is the named color it was set from.
+        theme.names.remove("background");
+        theme.update_surfaces()?;
+        Ok(theme)
+    }
+
+    /// The palette this theme was created from, e.g. to write it as a spec.
+    pub fn payload(&self) -> ThemeCreatePayload {
+        ThemeCreatePayload {
+            name: self.name.clone(),
+            brightness: self.brightness.clone(),
+            accent: self.accent,
+            background: self.background,
+            foreground: self.foreground,
+            colors: self.colors.clone(),
+            names: self.names.clone(),
+            tokens: self.tokens.clone(),
+            terminal: self.terminal.clone(),
+            diagnostics: self.diagnostics.clone(),
+            git: self.git.clone(),
+            derived: self.derived.clone(),
+        }
+    }
+
+    /// Evaluates the derived colors again after palette colors changed.
+    pub fn update_surfaces(&mut self) -> Result<(), String> {
+        self.surfaces = self.payload().surfaces()?;
+        Ok(())
+    }
+
     pub fn secondary_bg(&self) -> Color {
-        let bg = self.foreground;
-        self.background.mix(bg, 0.025)
+        self.surfaces.secondary_bg
     }
     pub fn border(&self) -> Color {
-        self.accent.mix(self.background, 0.9)
+        self.surfaces.border
+    }
+    pub fn doc_comment(&self) -> Color {
+        self.surfaces.doc_comment
     }
 }
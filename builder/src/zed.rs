@@ -0,0 +1,216 @@
+use serde_json::Value;
+
+use crate::{
+    color::Color,
+    import::ImportedPalette,
+    theme::{Brightness, Theme},
+};
+
+/// A theme recovered from Zed theme JSON, with everything the model could not
+/// reproduce.
+pub struct ParsedTheme {
+    pub theme: Theme,
+    pub warnings: Vec<String>,
+}
+
+/// Token roles and the syntax captures they are serialized to, first match wins.
+const TOKEN_CAPTURES: [(&str, &[&str]); 9] = [
+    ("brackets", &["punctuation.bracket"]),
+    ("comment", &["comment"]),
+    ("strings", &["string"]),
+    ("types", &["type"]),
+    ("functions", &["function"]),
+    ("properties", &["attribute"]),
+    ("keywords", &["keyword"]),
+    ("constants", &["number", "boolean"]),
+    ("operators", &["operator"]),
+];
+
+/// Reads a Zed theme family (`{"themes": [...]}`) such as `themes/theme.json`
+/// back into the Theme model.
+pub fn parse(source: &str) -> Result<Vec<ParsedTheme>, String> {
+    let document: Value = serde_json::from_str(source).map_err(|error| error.to_string())?;
+    let themes = document["themes"]
+        .as_array()
+        .ok_or("Expected a theme family with a \"themes\" array")?;
+
+    themes.iter().map(parse_theme).collect()
+}
+
+fn parse_theme(value: &Value) -> Result<ParsedTheme, String> {
+    let name = value["name"].as_str().unwrap_or("Untitled").to_string();
+    let style = &value["style"];
+    let syntax = &style["syntax"];
+    let color = |keys: &[&str]| {
+        keys.iter()
+            .find_map(|key| style[key].as_str().and_then(Color::parse))
+    };
+    let mut warnings = vec![];
+
+    let mut imported = ImportedPalette {
+        name: Some(name.clone()),
+        brightness: match value["appearance"].as_str() {
+            Some("light") => Some(Brightness::Light),
+            Some("dark") => Some(Brightness::Dark),
+            _ => None,
+        },
+        accent: color(&["accent", "text.accent"]),
+        background: color(&["editor.background", "background"]),
+        foreground: color(&["editor.foreground", "text"]),
+        ..Default::default()
+    };
+
+    for (role, captures) in TOKEN_CAPTURES {
+        let found = captures
+            .iter()
+            .find_map(|capture| syntax[capture]["color"].as_str().and_then(Color::parse));
+        match found {
+            Some(color) => {
+                imported.tokens.insert(role, color);
+            }
+            None => warnings.push(format!(
+                "tokens.{} is not in the JSON ({}), it was inferred",
+                role,
+                captures.join(", ")
+            )),
+        }
+    }
+    for (role, key) in crate::base16::TERMINAL_SLOTS
+        .iter()
+        .flat_map(|(role, _, bright, _)| [*role, *bright])
+        .map(|role| (role, format!("terminal.ansi.{}", role)))
+    {
+        match color(&[&key]) {
+            Some(color) => {
+                imported.terminal.insert(role, color);
+            }
+            None => warnings.push(format!("{} is missing, it was inferred", key)),
+        }
+    }
+    for (role, keys) in [
+        ("added", ["version_control.added", "created"]),
+        ("modified", ["version_control.modified", "modified"]),
+        ("removed", ["version_control.deleted", "deleted"]),
+    ] {
+        if let Some(color) = color(&keys) {
+            imported.git.insert(role, color);
+        }
+    }
+    for role in ["error", "warning", "info"] {
+        if let Some(color) = color(&[role]) {
+            imported.diagnostics.insert(role, color);
+        }
+    }
+
+    let mut theme = Theme::new(imported.complete(name)?)?;
+    theme.is_background_syntax = syntax.as_object().is_some_and(|syntax| {
+        syntax
+            .values()
+            .any(|style| style["background_color"].is_string())
+    });
+
+    // Whatever the model serializes differently was hand-edited or comes from
+    // another generator, and would be lost by adopting the recovered palette.
+    let rebuilt = serde_json::to_value(&theme).unwrap();
+    compare(&mut warnings, "", style, &rebuilt["style"]);
+
+    Ok(ParsedTheme { theme, warnings })
+}
+
+fn same(original: &Value, rebuilt: &Value) -> bool {
+    match (original, rebuilt) {
+        (Value::String(a), Value::String(b)) => match (Color::parse(a), Color::parse(b)) {
+            (Some(a), Some(b)) => a == b,
+            _ => a == b,
+        },
+        _ => original == rebuilt,
+    }
+}
+
+fn compare(warnings: &mut Vec<String>, path: &str, original: &Value, rebuilt: &Value) {
+    match (original, rebuilt) {
+        (Value::Object(original), _) => {
+            for (key, value) in original {
+                let path = match path {
+                    "" => key.clone(),
+                    path => format!("{}.{}", path, key),
+                };
+                compare(warnings, &path, value, &rebuilt[key]);
+            }
+        }
+        (Value::Array(original), _) => {
+            for (index, value) in original.iter().enumerate() {
+                compare(
+                    warnings,
+                    &format!("{}[{}]", path, index),
+                    value,
+                    &rebuilt[index],
+                );
+            }
+        }
+        (_, Value::Null) if !original.is_null() => {
+            warnings.push(format!(
+                "{} = {} cannot be expressed in the model",
+                path, original
+            ));
+        }
+        _ if !same(original, rebuilt) => {
+            warnings.push(format!(
+                "{} = {} in the JSON, but the model produces {}",
+                path, original, rebuilt
+            ));
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::ZedExporter;
+
+    #[test]
+    fn recovers_the_published_themes() {
+        let package = crate::snowfall();
+        let parsed = parse(&ZedExporter::json(&package)).unwrap();
+
+        assert_eq!(parsed.len(), package.themes.len());
+        for (parsed, theme) in parsed.iter().zip(&package.themes) {
+            assert_eq!(parsed.theme.name, theme.name);
+            assert_eq!(
+                parsed.theme.is_background_syntax,
+                theme.is_background_syntax
+            );
+            assert_eq!(
+                serde_json::to_value(&parsed.theme).unwrap(),
+                serde_json::to_value(theme).unwrap()
+            );
+            assert_eq!(
+                parsed.warnings,
+                vec!["tokens.operators is not in the JSON (operator), it was inferred"]
+            );
+        }
+    }
+
+    #[test]
+    fn warns_about_hand_edits() {
+        let package = crate::snowfall();
+        let mut document: Value = serde_json::from_str(&ZedExporter::json(&package)).unwrap();
+        let style = &mut document["themes"][0]["style"];
+        style["tab.active_background"] = "#ff0000".into();
+        style["editor.indent_guide"] = "#00ff00".into();
+        style["syntax"]["keyword"]["color"] = "#123456".into();
+
+        let parsed = parse(&document.to_string()).unwrap();
+        let theme = &parsed[0];
+
+        assert_eq!(theme.theme.tokens.keywords, Color::from_hex("#123456"));
+        assert!(theme.warnings.contains(
+            &"editor.indent_guide = \"#00ff00\" cannot be expressed in the model".into()
+        ));
+        assert!(theme.warnings.contains(
+            &"tab.active_background = \"#ff0000\" in the JSON, but the model produces \"#24272d\""
+                .into()
+        ));
+    }
+}